@@ -0,0 +1,513 @@
+use proc_macro2::{Punct, Spacing, Span, TokenStream};
+use quote::{ToTokens, TokenStreamExt};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, token, Expr, Ident, LitStr, Pat, Result, Token};
+
+/// A tag or attribute name. Hyphenated names such as `aria-label` or
+/// `my-widget` are written as plain tokens and joined here.
+pub struct Name {
+    pub parts: Vec<Ident>,
+}
+
+impl Name {
+    pub fn value(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| part.unraw().to_string())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    pub fn span(&self) -> Span {
+        self.parts[0].span()
+    }
+}
+
+impl Parse for Name {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut parts = vec![Ident::parse_any(input)?];
+        while input.peek(Token![-]) && input.peek2(Ident::peek_any) {
+            input.parse::<Token![-]>()?;
+            parts.push(Ident::parse_any(input)?);
+        }
+        Ok(Name { parts })
+    }
+}
+
+impl ToTokens for Name {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                let mut dash = Punct::new('-', Spacing::Alone);
+                dash.set_span(part.span());
+                tokens.append(dash);
+            }
+            part.to_tokens(tokens);
+        }
+    }
+}
+
+/// The whole `rsx!` input: a single root element, or `fragment { }` for
+/// several siblings.
+pub struct Root {
+    pub element: Element,
+}
+
+impl Parse for Root {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let element = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("rsx! expects a single root element; wrap siblings in `fragment { }`"));
+        }
+        Ok(Root { element })
+    }
+}
+
+/// `tag { items }`
+pub struct Element {
+    pub name: Name,
+    pub items: Vec<Item>,
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        let content;
+        braced!(content in input);
+        let items = parse_items(&content)?;
+        Ok(Element { name, items })
+    }
+}
+
+// Only lives for the duration of one expansion, so variant sizes don't matter
+#[allow(clippy::large_enum_variant)]
+pub enum Item {
+    /// `name = value` or `"name" = value`
+    Attribute(Attribute),
+    /// `.name = value`
+    Property(Property),
+    /// `class:name = condition`
+    ClassToggle(ClassToggle),
+    /// `style = { property: value, ... }`
+    Style(Style),
+    /// `event => handler`
+    Event(Event),
+    Child(Child),
+}
+
+impl Item {
+    fn ends_in_block(&self) -> bool {
+        matches!(
+            self,
+            Item::Child(
+                Child::Element(_) | Child::If(_) | Child::Match(_) | Child::For(_) | Child::Call(_)
+            )
+        )
+    }
+}
+
+pub struct Attribute {
+    pub name: AttributeName,
+    pub value: Expr,
+}
+
+pub enum AttributeName {
+    /// Checked against the spec for the element it is set on
+    Name(Name),
+    /// Passed through as is
+    Literal(LitStr),
+}
+
+pub struct Property {
+    pub name: Ident,
+    pub value: Expr,
+}
+
+pub struct ClassToggle {
+    pub name: Name,
+    pub condition: Expr,
+}
+
+pub struct Style {
+    pub name: Name,
+    pub declarations: Vec<Declaration>,
+}
+
+/// `property: value` inside a style map
+pub struct Declaration {
+    pub property: StyleProperty,
+    pub value: Expr,
+}
+
+pub enum StyleProperty {
+    /// `background-color`
+    Name(Name),
+    /// `"--accent"`, for names that aren't Rust tokens
+    Literal(LitStr),
+}
+
+impl StyleProperty {
+    pub fn value(&self) -> String {
+        match self {
+            StyleProperty::Name(name) => name.value(),
+            StyleProperty::Literal(literal) => literal.value(),
+        }
+    }
+}
+
+impl Parse for Declaration {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let property = if input.peek(LitStr) {
+            StyleProperty::Literal(input.parse()?)
+        } else {
+            StyleProperty::Name(input.parse()?)
+        };
+        input.parse::<Token![:]>()?;
+        let value = input.parse()?;
+        Ok(Declaration { property, value })
+    }
+}
+
+pub struct Event {
+    pub name: EventName,
+    pub handler: Expr,
+}
+
+pub enum EventName {
+    /// Checked against the known DOM events, which pick the handler's type
+    Name(Name),
+    /// Custom events: passed through as is, handled as a plain `Event`
+    Literal(LitStr),
+}
+
+impl EventName {
+    pub fn value(&self) -> String {
+        match self {
+            EventName::Name(name) => name.value(),
+            EventName::Literal(literal) => literal.value(),
+        }
+    }
+}
+
+impl ToTokens for EventName {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            EventName::Name(name) => name.to_tokens(tokens),
+            EventName::Literal(literal) => literal.to_tokens(tokens),
+        }
+    }
+}
+
+pub enum Child {
+    Element(Element),
+    /// `"text"`
+    Text(LitStr),
+    /// `@expr`, rendered through `Display`
+    TextExpr(Expr),
+    If(If),
+    Match(Match),
+    For(For),
+    /// `component(args) { items }`
+    Call(Call),
+    /// Any expression producing a node
+    Expr(Expr),
+}
+
+/// A function or method call followed by a block, which is passed to it as
+/// a last `Children` argument: the block's children, and the items of each
+/// `name: { items }` in it as the slot `name`.
+pub struct Call {
+    pub call: Expr,
+    pub children: Vec<Item>,
+    pub slots: Vec<Slot>,
+}
+
+/// `name: { items }` inside the block of a `Call`
+pub struct Slot {
+    pub name: Ident,
+    pub items: Vec<Item>,
+}
+
+/// `if cond { items } else if cond { items } else { items }`
+pub struct If {
+    pub condition: Condition,
+    pub then_branch: Vec<Item>,
+    pub else_branch: Option<Else>,
+}
+
+pub enum Condition {
+    Expr(Expr),
+    Let(Pat, Expr),
+}
+
+impl ToTokens for Condition {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Condition::Expr(expr) => expr.to_tokens(tokens),
+            Condition::Let(pat, expr) => {
+                <Token![let]>::default().to_tokens(tokens);
+                pat.to_tokens(tokens);
+                <Token![=]>::default().to_tokens(tokens);
+                expr.to_tokens(tokens);
+            }
+        }
+    }
+}
+
+pub enum Else {
+    If(Box<If>),
+    Block(Vec<Item>),
+}
+
+/// `match expr { Pat => { items }, Pat => item }`
+pub struct Match {
+    pub expr: Expr,
+    pub arms: Vec<Arm>,
+}
+
+pub struct Arm {
+    pub pat: Pat,
+    pub guard: Option<Expr>,
+    pub body: Vec<Item>,
+}
+
+/// `for pat in iter { items }`
+pub struct For {
+    pub pat: Pat,
+    pub iter: Expr,
+    pub body: Vec<Item>,
+}
+
+/// Parses comma separated items. The comma is optional after items that
+/// end in a brace block (elements, `if`, `match` and `for`).
+pub fn parse_items(input: ParseStream) -> Result<Vec<Item>> {
+    let mut items = Vec::new();
+    while !input.is_empty() {
+        let item = parse_item(input)?;
+        let ends_in_block = item.ends_in_block();
+        items.push(item);
+
+        if input.is_empty() {
+            break;
+        }
+        if ends_in_block {
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        } else {
+            input.parse::<Token![,]>()?;
+        }
+    }
+    Ok(items)
+}
+
+fn parse_item(input: ParseStream) -> Result<Item> {
+    // `=` also matches the first half of `=>`, so events are always checked first
+    if input.peek(LitStr) && input.peek2(Token![=]) && !input.peek2(Token![=>]) {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        return Ok(Item::Attribute(Attribute { name: AttributeName::Literal(name), value }));
+    }
+
+    if input.peek(LitStr) && input.peek2(Token![=>]) {
+        let name = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let handler = input.parse()?;
+        return Ok(Item::Event(Event { name: EventName::Literal(name), handler }));
+    }
+
+    if input.peek(Token![.]) {
+        input.parse::<Token![.]>()?;
+        let name = Ident::parse_any(input)?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        return Ok(Item::Property(Property { name, value }));
+    }
+
+    if input.peek(Ident::peek_any) && !starts_control_flow(input) {
+        let fork = input.fork();
+        let name: Name = fork.parse()?;
+        if fork.peek(Token![=>]) {
+            input.parse::<Name>()?;
+            input.parse::<Token![=>]>()?;
+            let handler = input.parse()?;
+            return Ok(Item::Event(Event { name: EventName::Name(name), handler }));
+        }
+        if name.value() == "class" && fork.peek(Token![:]) && !fork.peek(Token![::]) {
+            input.parse::<Name>()?;
+            input.parse::<Token![:]>()?;
+            let name = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let condition = input.parse()?;
+            return Ok(Item::ClassToggle(ClassToggle { name, condition }));
+        }
+        if fork.peek(Token![=]) && !fork.peek(Token![==]) {
+            input.parse::<Name>()?;
+            input.parse::<Token![=]>()?;
+            if name.value() == "style" && input.peek(token::Brace) {
+                let content;
+                braced!(content in input);
+                let declarations = content.parse_terminated(Declaration::parse, Token![,])?;
+                let declarations = declarations.into_iter().collect();
+                return Ok(Item::Style(Style { name, declarations }));
+            }
+            let value = input.parse()?;
+            return Ok(Item::Attribute(Attribute { name: AttributeName::Name(name), value }));
+        }
+        if fork.peek(token::Brace) {
+            return Ok(Item::Child(Child::Element(input.parse()?)));
+        }
+    }
+
+    parse_child(input).map(Item::Child)
+}
+
+/// `if`, `match` and `for` open control flow, except for the `for` attribute of
+/// `<label>` and `<output>`.
+fn starts_control_flow(input: ParseStream) -> bool {
+    input.peek(Token![if])
+        || input.peek(Token![match])
+        || (input.peek(Token![for]) && !input.peek2(Token![=]))
+}
+
+fn parse_child(input: ParseStream) -> Result<Child> {
+    if input.peek(LitStr) {
+        return Ok(Child::Text(input.parse()?));
+    }
+    if input.peek(Token![@]) {
+        input.parse::<Token![@]>()?;
+        return Ok(Child::TextExpr(input.parse()?));
+    }
+    if input.peek(Token![if]) {
+        return Ok(Child::If(parse_if(input)?));
+    }
+    if input.peek(Token![match]) {
+        return Ok(Child::Match(parse_match(input)?));
+    }
+    if input.peek(Token![for]) {
+        return Ok(Child::For(parse_for(input)?));
+    }
+    let expr = input.parse()?;
+    if matches!(expr, Expr::Call(_) | Expr::MethodCall(_)) && input.peek(token::Brace) {
+        return Ok(Child::Call(parse_call(expr, input)?));
+    }
+    Ok(Child::Expr(expr))
+}
+
+/// The block after a call. Like `parse_items`, except that `name: { items }`
+/// fills a slot.
+fn parse_call(call: Expr, input: ParseStream) -> Result<Call> {
+    let content;
+    braced!(content in input);
+    let mut children = Vec::new();
+    let mut slots = Vec::new();
+    while !content.is_empty() {
+        let ends_in_block = if content.peek(Ident) && content.peek2(Token![:]) && !content.peek2(Token![::]) {
+            let name = content.parse()?;
+            content.parse::<Token![:]>()?;
+            slots.push(Slot { name, items: parse_block(&content)? });
+            true
+        } else {
+            let item = parse_item(&content)?;
+            let ends_in_block = item.ends_in_block();
+            children.push(item);
+            ends_in_block
+        };
+
+        if content.is_empty() {
+            break;
+        }
+        if ends_in_block {
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        } else {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(Call { call, children, slots })
+}
+
+fn parse_block(input: ParseStream) -> Result<Vec<Item>> {
+    let content;
+    braced!(content in input);
+    parse_items(&content)
+}
+
+fn parse_if(input: ParseStream) -> Result<If> {
+    input.parse::<Token![if]>()?;
+    let condition = if input.peek(Token![let]) {
+        input.parse::<Token![let]>()?;
+        let pat = Pat::parse_multi_with_leading_vert(input)?;
+        input.parse::<Token![=]>()?;
+        Condition::Let(pat, Expr::parse_without_eager_brace(input)?)
+    } else {
+        Condition::Expr(Expr::parse_without_eager_brace(input)?)
+    };
+    let then_branch = parse_block(input)?;
+
+    let else_branch = if input.peek(Token![else]) {
+        input.parse::<Token![else]>()?;
+        if input.peek(Token![if]) {
+            Some(Else::If(Box::new(parse_if(input)?)))
+        } else {
+            Some(Else::Block(parse_block(input)?))
+        }
+    } else {
+        None
+    };
+
+    Ok(If { condition, then_branch, else_branch })
+}
+
+fn parse_match(input: ParseStream) -> Result<Match> {
+    input.parse::<Token![match]>()?;
+    let expr = Expr::parse_without_eager_brace(input)?;
+
+    let content;
+    braced!(content in input);
+    let mut arms = Vec::new();
+    while !content.is_empty() {
+        let pat = Pat::parse_multi_with_leading_vert(&content)?;
+        let guard = if content.peek(Token![if]) {
+            content.parse::<Token![if]>()?;
+            Some(content.parse()?)
+        } else {
+            None
+        };
+        content.parse::<Token![=>]>()?;
+
+        let (body, ends_in_block) = if content.peek(token::Brace) {
+            (parse_block(&content)?, true)
+        } else {
+            let item = parse_item(&content)?;
+            let ends_in_block = item.ends_in_block();
+            (vec![item], ends_in_block)
+        };
+        arms.push(Arm { pat, guard, body });
+
+        if content.is_empty() {
+            break;
+        }
+        if ends_in_block {
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        } else {
+            content.parse::<Token![,]>()?;
+        }
+    }
+
+    Ok(Match { expr, arms })
+}
+
+fn parse_for(input: ParseStream) -> Result<For> {
+    input.parse::<Token![for]>()?;
+    let pat = Pat::parse_multi_with_leading_vert(input)?;
+    input.parse::<Token![in]>()?;
+    let iter = Expr::parse_without_eager_brace(input)?;
+    let body = parse_block(input)?;
+    Ok(For { pat, iter, body })
+}
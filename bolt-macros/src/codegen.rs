@@ -0,0 +1,890 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+use syn::ext::IdentExt;
+use syn::{parse_quote, Error, Expr, Ident, Lit};
+
+use crate::ast::{
+    Attribute, AttributeName, Call, Child, ClassToggle, Element, Else, Event, EventName, For, If, Item,
+    Match, Name, Root, Style,
+};
+use crate::spec::{self, Namespace};
+
+/// Tag name of the fragment pseudo-element, which groups siblings without a wrapper.
+const FRAGMENT: &str = "fragment";
+
+/// `ref = node_ref` binds the element to a `NodeRef` instead of setting an attribute.
+const REF: &str = "ref";
+
+/// `class` goes through the element's `DomTokenList`, so it composes with `class:name`.
+const CLASS: &str = "class";
+
+/// `key = id` identifies a list item across renders instead of setting an attribute.
+const KEY: &str = "key";
+
+pub fn expand(root: &Root) -> TokenStream {
+    let mut generator = Generator::default();
+    let tokens = generator.root(root);
+    match generator.errors {
+        Some(errors) => {
+            let errors = errors.to_compile_error();
+            quote!({ #errors })
+        }
+        None => tokens,
+    }
+}
+
+/// What the items of an element are being generated against.
+struct Scope<'a> {
+    /// Local holding the element under construction
+    elem: &'a Ident,
+    tag: &'a str,
+    /// Namespace the element itself lives in
+    ns: Namespace,
+    /// Namespace its child elements are created in
+    children_ns: Namespace,
+}
+
+/// A dynamic part of a templated subtree, filled in on every render.
+enum Hole<'a> {
+    /// Items that need the element itself: events, refs, properties and
+    /// attributes whose value isn't a literal
+    Items { path: Vec<usize>, scope: HoleScope, items: Vec<&'a Item> },
+    /// `@expr`, written into an empty text node
+    Text { path: Vec<usize>, expr: &'a Expr },
+    /// A run of control flow and node expressions, rendered in front of a
+    /// comment marker
+    Children { path: Vec<usize>, scope: HoleScope, items: Vec<&'a Item> },
+}
+
+/// Owned version of `Scope` for the element a hole belongs to.
+struct HoleScope {
+    tag: String,
+    ns: Namespace,
+    children_ns: Namespace,
+}
+
+#[derive(Default)]
+struct Generator {
+    errors: Option<Error>,
+}
+
+impl Generator {
+    fn error(&mut self, error: Error) {
+        match &mut self.errors {
+            Some(errors) => errors.combine(error),
+            None => self.errors = Some(error),
+        }
+    }
+
+    fn root(&mut self, root: &Root) -> TokenStream {
+        if root.element.name.value() == FRAGMENT {
+            self.fragment(&root.element)
+        } else {
+            self.element(&root.element, Namespace::Html)
+        }
+    }
+
+    fn element(&mut self, element: &Element, parent_ns: Namespace) -> TokenStream {
+        if is_fixed_shape(element) && static_elements(element) >= MIN_TEMPLATE_ELEMENTS {
+            self.template(element, parent_ns)
+        } else {
+            self.direct_element(element, parent_ns)
+        }
+    }
+
+    /// A `VElement` that spells out every item, diffed in full on each render.
+    fn direct_element(&mut self, element: &Element, parent_ns: Namespace) -> TokenStream {
+        let tag = element.name.value();
+        let ns = parent_ns.for_tag(&tag);
+        self.check_element(&element.name, &tag, ns);
+
+        // Every element block shadows the same local, so nested elements can
+        // share one name. Mixed-site hygiene keeps it out of user expressions.
+        let elem = Ident::new("elem", Span::mixed_site());
+        let scope = Scope { elem: &elem, tag: &tag, ns, children_ns: ns.for_children(&tag) };
+        let items = self.items(&element.items, &scope);
+        let ns_tokens = namespace_tokens(ns);
+
+        quote! {{
+            #[allow(unused_mut)]
+            let mut #elem = ::bolt::vdom::VElement::new(#ns_tokens, #tag);
+            #items
+            ::bolt::vdom::VNode::from(#elem)
+        }}
+    }
+
+    /// A `VTemplate`: the static part of the subtree is laid out once, at
+    /// compile time, and only the holes are built and diffed on each render.
+    fn template(&mut self, element: &Element, parent_ns: Namespace) -> TokenStream {
+        let mut holes = Vec::new();
+        let mut key = None;
+        let skeleton = self.skeleton(element, parent_ns, &mut Vec::new(), &mut holes, &mut key);
+
+        let holes_ident = Ident::new("holes", Span::mixed_site());
+        let pushes: Vec<_> = holes.iter().map(|hole| self.hole(hole, &holes_ident)).collect();
+        let capacity = holes.len();
+        let key = match key {
+            Some(key) => quote_spanned! {key.span()=>
+                ::std::option::Option::Some(::std::string::ToString::to_string(&(#key)))
+            },
+            None => quote!(::std::option::Option::None),
+        };
+
+        quote! {{
+            static RSX_SKELETON: ::bolt::vdom::StaticElement = #skeleton;
+            let mut #holes_ident = ::std::vec::Vec::with_capacity(#capacity);
+            #(#pushes)*
+            ::bolt::vdom::VNode::template(&RSX_SKELETON, #key, #holes_ident)
+        }}
+    }
+
+    /// The static part of a fixed-shape element: its tag, literal attributes,
+    /// text and fixed-shape children. Everything else is recorded in `holes`,
+    /// with the path of child indices leading to it.
+    fn skeleton<'a>(
+        &mut self,
+        element: &'a Element,
+        parent_ns: Namespace,
+        path: &mut Vec<usize>,
+        holes: &mut Vec<Hole<'a>>,
+        key: &mut Option<&'a Expr>,
+    ) -> TokenStream {
+        let tag = element.name.value();
+        let ns = parent_ns.for_tag(&tag);
+        self.check_element(&element.name, &tag, ns);
+
+        let elem = Ident::new("elem", Span::mixed_site());
+        let scope = Scope { elem: &elem, tag: &tag, ns, children_ns: ns.for_children(&tag) };
+        let hole_scope = || HoleScope { tag: tag.clone(), ns, children_ns: scope.children_ns };
+
+        let mut items = Vec::new();
+        self.flatten(&element.items, &mut items);
+
+        let mut attributes = Vec::new();
+        let mut children = Vec::new();
+        let mut element_items = Vec::new();
+        let mut run = Vec::new();
+        for item in items {
+            let child = match item {
+                Item::Attribute(attribute) if is_key(attribute) && path.is_empty() => {
+                    *key = Some(&attribute.value);
+                    continue;
+                }
+                Item::Attribute(attribute) if is_static_attribute(attribute) => {
+                    if let Some(attribute) = self.static_attribute(attribute, &scope) {
+                        attributes.push(attribute);
+                    }
+                    continue;
+                }
+                Item::Child(child) => child,
+                _ => {
+                    element_items.push(item);
+                    continue;
+                }
+            };
+            if spec::is_void_element(ns, &tag) {
+                self.error(Error::new(
+                    child_span(child),
+                    format!("`<{}>` is a void element and cannot have children", tag),
+                ));
+                continue;
+            }
+
+            let is_static = match child {
+                Child::Element(element) => is_fixed_shape(element) && !has_key(element),
+                Child::Text(_) | Child::TextExpr(_) => true,
+                _ => false,
+            };
+            if !is_static {
+                run.push(item);
+                continue;
+            }
+            if !run.is_empty() {
+                children.push(marker(path, children.len(), hole_scope(), &mut run, holes));
+            }
+
+            path.push(children.len());
+            children.push(match child {
+                Child::Element(element) => {
+                    let child = self.skeleton(element, scope.children_ns, path, holes, key);
+                    quote!(::bolt::vdom::StaticNode::Element(#child))
+                }
+                Child::Text(text) => quote!(::bolt::vdom::StaticNode::Text(#text)),
+                Child::TextExpr(expr) => {
+                    holes.push(Hole::Text { path: path.clone(), expr });
+                    quote!(::bolt::vdom::StaticNode::Text(""))
+                }
+                _ => unreachable!(),
+            });
+            path.pop();
+        }
+        if !run.is_empty() {
+            children.push(marker(path, children.len(), hole_scope(), &mut run, holes));
+        }
+        if !element_items.is_empty() {
+            holes.push(Hole::Items { path: path.clone(), scope: hole_scope(), items: element_items });
+        }
+
+        let ns_tokens = namespace_tokens(ns);
+        let attributes = attributes.iter().map(|(name, value)| quote!((#name, #value)));
+        quote! {
+            ::bolt::vdom::StaticElement {
+                ns: #ns_tokens,
+                tag: #tag,
+                attributes: &[#(#attributes),*],
+                children: &[#(#children),*],
+            }
+        }
+    }
+
+    /// Name and value of a literal attribute, or `None` for `false`.
+    fn static_attribute(&mut self, attribute: &Attribute, scope: &Scope) -> Option<(String, String)> {
+        let name = match &attribute.name {
+            AttributeName::Name(name) if name.value() == CLASS => name.value(),
+            AttributeName::Name(name) => {
+                self.check_attribute(name, scope);
+                name.value()
+            }
+            AttributeName::Literal(literal) => literal.value(),
+        };
+        let value = match &attribute.value {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Str(value) if name == CLASS => {
+                    value.value().split_whitespace().collect::<Vec<_>>().join(" ")
+                }
+                Lit::Str(value) => value.value(),
+                Lit::Int(value) => value.base10_digits().to_string(),
+                Lit::Bool(value) if value.value => String::new(),
+                Lit::Bool(_) => return None,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        if name == CLASS && value.is_empty() {
+            return None;
+        }
+        Some((name, value))
+    }
+
+    /// Builds one hole and pushes it onto `holes`.
+    fn hole(&mut self, hole: &Hole, holes: &Ident) -> TokenStream {
+        let elem = Ident::new("elem", Span::mixed_site());
+        match hole {
+            Hole::Items { path, scope, items } => {
+                let scope = Scope {
+                    elem: &elem,
+                    tag: &scope.tag,
+                    ns: scope.ns,
+                    children_ns: scope.children_ns,
+                };
+                let items: TokenStream = items.iter().map(|item| self.item(item, &scope)).collect();
+                let path = path_tokens(path);
+                quote! {{
+                    let mut #elem = ::bolt::vdom::ElementItems::default();
+                    #items
+                    #holes.push(::bolt::vdom::Hole::element(#path, #elem));
+                }}
+            }
+            Hole::Text { path, expr } => {
+                let path = path_tokens(path);
+                quote_spanned! {expr.span()=>
+                    #holes.push(::bolt::vdom::Hole::text(#path, ::bolt::rsx::text(&(#expr))));
+                }
+            }
+            Hole::Children { path, scope, items } => {
+                let scope = Scope {
+                    elem: &elem,
+                    tag: &scope.tag,
+                    ns: scope.ns,
+                    children_ns: scope.children_ns,
+                };
+                let items: TokenStream = items.iter().map(|item| self.item(item, &scope)).collect();
+                let path = path_tokens(path);
+                quote! {{
+                    let mut #elem = ::std::vec::Vec::new();
+                    #items
+                    #holes.push(::bolt::vdom::Hole::children(#path, #elem));
+                }}
+            }
+        }
+    }
+
+    /// Items of an element with nested fragments spliced in, checking the
+    /// fragments on the way.
+    fn flatten<'a>(&mut self, items: &'a [Item], out: &mut Vec<&'a Item>) {
+        for item in items {
+            match item {
+                Item::Child(Child::Element(element)) if element.name.value() == FRAGMENT => {
+                    self.check_fragment(element);
+                    let children = element.items.iter().filter(|item| matches!(item, Item::Child(_)));
+                    for child in children {
+                        self.flatten(std::slice::from_ref(child), out);
+                    }
+                }
+                _ => out.push(item),
+            }
+        }
+    }
+
+    /// A root `fragment { }` builds a `VNode::Fragment`, so an `rsx!` call can
+    /// return several siblings.
+    fn fragment(&mut self, fragment: &Element) -> TokenStream {
+        self.check_fragment(fragment);
+        self.node_list(&fragment.items, Namespace::Html)
+    }
+
+    /// Children as one `VNode::Fragment`, created in `ns`.
+    fn node_list(&mut self, items: &[Item], ns: Namespace) -> TokenStream {
+        let elem = Ident::new("elem", Span::mixed_site());
+        let scope = Scope { elem: &elem, tag: FRAGMENT, ns, children_ns: ns };
+        let items = self.items(items, &scope);
+
+        quote! {{
+            #[allow(unused_mut)]
+            let mut #elem = ::std::vec::Vec::new();
+            #items
+            ::bolt::vdom::VNode::Fragment(#elem)
+        }}
+    }
+
+    /// Nested fragments need no node of their own: their children go straight
+    /// into the enclosing parent.
+    fn fragment_items(&mut self, fragment: &Element, scope: &Scope) -> TokenStream {
+        self.check_fragment(fragment);
+        self.items(&fragment.items, scope)
+    }
+
+    fn check_fragment(&mut self, fragment: &Element) {
+        self.check_only_children(&fragment.items, "fragments only take children");
+    }
+
+    fn check_only_children(&mut self, items: &[Item], message: &str) {
+        for item in items {
+            let name = match item {
+                Item::Attribute(attribute) => match &attribute.name {
+                    AttributeName::Name(name) => name.to_token_stream(),
+                    AttributeName::Literal(literal) => literal.to_token_stream(),
+                },
+                Item::Property(property) => property.name.to_token_stream(),
+                Item::ClassToggle(toggle) => toggle.name.to_token_stream(),
+                Item::Style(style) => style.name.to_token_stream(),
+                Item::Event(event) => event.name.to_token_stream(),
+                Item::Child(_) => continue,
+            };
+            self.error(Error::new_spanned(name, message));
+        }
+    }
+
+    fn items(&mut self, items: &[Item], scope: &Scope) -> TokenStream {
+        items.iter().map(|item| self.item(item, scope)).collect()
+    }
+
+    fn item(&mut self, item: &Item, scope: &Scope) -> TokenStream {
+        let elem = scope.elem;
+        match item {
+            Item::Attribute(attribute) => self.attribute(attribute, scope),
+            Item::Property(property) => {
+                let name = property.name.to_string();
+                let value = &property.value;
+                quote_spanned! {value.span()=>
+                    ::bolt::rsx::set_property(&mut #elem, #name, #value);
+                }
+            }
+            Item::ClassToggle(toggle) => class_toggle(toggle, elem),
+            Item::Style(style) => style_map(style, elem),
+            Item::Event(event) => self.event(event, scope),
+            Item::Child(child) => {
+                if spec::is_void_element(scope.ns, scope.tag) {
+                    self.error(Error::new(
+                        child_span(child),
+                        format!("`<{}>` is a void element and cannot have children", scope.tag),
+                    ));
+                }
+                self.child(child, scope)
+            }
+        }
+    }
+
+    fn attribute(&mut self, attribute: &Attribute, scope: &Scope) -> TokenStream {
+        let elem = scope.elem;
+        let value = &attribute.value;
+        let name = match &attribute.name {
+            AttributeName::Name(name) if name.value() == REF => {
+                return quote_spanned! {value.span()=>
+                    ::bolt::rsx::bind_ref(&mut #elem, &(#value));
+                };
+            }
+            AttributeName::Name(name) if name.value() == KEY => {
+                return quote_spanned! {value.span()=>
+                    ::bolt::rsx::set_key(&mut #elem, &(#value));
+                };
+            }
+            AttributeName::Name(name) if name.value() == CLASS => {
+                return quote_spanned! {value.span()=>
+                    ::bolt::rsx::add_class(&mut #elem, &(#value));
+                };
+            }
+            AttributeName::Name(name) => {
+                self.check_attribute(name, scope);
+                name.value()
+            }
+            AttributeName::Literal(literal) => literal.value(),
+        };
+        quote_spanned! {value.span()=>
+            ::bolt::rsx::set_attribute(&mut #elem, #name, &(#value));
+        }
+    }
+
+    fn event(&mut self, event: &Event, scope: &Scope) -> TokenStream {
+        let elem = scope.elem;
+        let name = event.name.value();
+        let handler = &event.handler;
+        let ty = match (&event.name, spec::event_type(&name)) {
+            (EventName::Name(_), Some(ty)) => format_ident!("{}", ty),
+            (EventName::Name(_), None) => {
+                self.error(Error::new_spanned(
+                    &event.name,
+                    format!(
+                        "unknown event `{}`; write custom events as a string literal, `\"{}\" => handler`",
+                        name, name
+                    ),
+                ));
+                format_ident!("Event")
+            }
+            (EventName::Literal(_), _) => format_ident!("Event"),
+        };
+        quote_spanned! {handler.span()=>
+            ::bolt::rsx::add_event_listener::<::bolt::rsx::web_sys::#ty, _>(&mut #elem, #name, #handler);
+        }
+    }
+
+    fn child(&mut self, child: &Child, scope: &Scope) -> TokenStream {
+        let elem = scope.elem;
+        match child {
+            Child::Element(element) if element.name.value() == FRAGMENT => {
+                self.fragment_items(element, scope)
+            }
+            Child::Element(element) => {
+                let child = self.element(element, scope.children_ns);
+                quote! {
+                    ::bolt::rsx::append_child(&mut #elem, #child);
+                }
+            }
+            Child::Text(text) => quote! {
+                ::bolt::rsx::append_text(&mut #elem, #text);
+            },
+            Child::TextExpr(expr) => quote_spanned! {expr.span()=>
+                ::bolt::rsx::append_child(&mut #elem, ::bolt::rsx::text(&(#expr)));
+            },
+            Child::If(if_node) => self.if_node(if_node, scope),
+            Child::Match(match_node) => self.match_node(match_node, scope),
+            Child::For(for_node) => self.for_node(for_node, scope),
+            Child::Call(call) => {
+                let call = self.call(call, scope.children_ns);
+                quote! {
+                    ::bolt::rsx::append_child(&mut #elem, #call);
+                }
+            }
+            Child::Expr(expr) => quote_spanned! {expr.span()=>
+                ::bolt::rsx::append_child(&mut #elem, #expr);
+            },
+        }
+    }
+
+    /// The call with its block appended to the arguments as a `Children`.
+    fn call(&mut self, call: &Call, ns: Namespace) -> TokenStream {
+        // A `Component` renders from `&Props` every time, and `Children`
+        // can only be placed once
+        if let Expr::Call(expr) = &call.call {
+            if let Expr::Path(path) = &*expr.func {
+                if path.path.segments.last().is_some_and(|segment| segment.ident == "component") {
+                    self.error(Error::new_spanned(
+                        &expr.func,
+                        "`component` can't take a block; give the component a `Fn() -> VNode` prop for its children, \
+                         or write it as a function that takes `Children`",
+                    ));
+                }
+            }
+        }
+        let message = "pass attributes and handlers to the component as arguments";
+        self.check_only_children(&call.children, message);
+        let children = self.node_list(&call.children, ns);
+
+        let mut filled = Vec::new();
+        let mut slots = Vec::new();
+        for slot in &call.slots {
+            let name = slot.name.unraw().to_string();
+            if filled.contains(&name) {
+                self.error(Error::new(slot.name.span(), format!("slot `{}` is filled twice", name)));
+            }
+            self.check_only_children(&slot.items, message);
+            let nodes = self.node_list(&slot.items, ns);
+            slots.push(quote_spanned! {slot.name.span()=> .with_slot(#name, #nodes) });
+            filled.push(name);
+        }
+
+        let children: Expr = parse_quote!(::bolt::vdom::Children::new(#children) #(#slots)*);
+        let mut call = call.call.clone();
+        match &mut call {
+            Expr::Call(call) => call.args.push(children),
+            Expr::MethodCall(call) => call.args.push(children),
+            _ => unreachable!("only calls take a block"),
+        }
+        call.into_token_stream()
+    }
+
+    fn if_node(&mut self, if_node: &If, scope: &Scope) -> TokenStream {
+        let condition = &if_node.condition;
+        let then_branch = self.items(&if_node.then_branch, scope);
+        let else_branch = match &if_node.else_branch {
+            Some(Else::If(else_if)) => {
+                let else_if = self.if_node(else_if, scope);
+                quote!(else #else_if)
+            }
+            Some(Else::Block(items)) => {
+                let items = self.items(items, scope);
+                quote!(else { #items })
+            }
+            None => quote!(),
+        };
+        quote! {
+            if #condition { #then_branch } #else_branch
+        }
+    }
+
+    fn match_node(&mut self, match_node: &Match, scope: &Scope) -> TokenStream {
+        let expr = &match_node.expr;
+        let arms = match_node.arms.iter().map(|arm| {
+            let pat = &arm.pat;
+            let guard = arm.guard.as_ref().map(|guard| quote!(if #guard));
+            let body = self.items(&arm.body, scope);
+            quote! {
+                #pat #guard => { #body }
+            }
+        });
+        let arms: Vec<_> = arms.collect();
+        quote! {
+            match #expr { #(#arms)* }
+        }
+    }
+
+    fn for_node(&mut self, for_node: &For, scope: &Scope) -> TokenStream {
+        let pat = &for_node.pat;
+        let iter = &for_node.iter;
+        let body = self.items(&for_node.body, scope);
+        quote! {
+            for #pat in #iter { #body }
+        }
+    }
+
+    fn check_element(&mut self, name: &Name, tag: &str, ns: Namespace) {
+        if ns == Namespace::Html && spec::is_custom_element(tag) {
+            return;
+        }
+        if !spec::is_known_element(ns, tag) {
+            let hint = if ns == Namespace::Html {
+                "; custom elements must contain a hyphen, e.g. `my-widget`"
+            } else {
+                ""
+            };
+            self.error(Error::new_spanned(
+                name,
+                format!("unknown {} element `<{}>`{}", ns.name(), tag, hint),
+            ));
+        }
+    }
+
+    fn check_attribute(&mut self, name: &Name, scope: &Scope) {
+        let attr = name.value();
+        if scope.ns == Namespace::Html && spec::is_custom_element(scope.tag) {
+            return;
+        }
+        if spec::is_known_attribute(scope.ns, scope.tag, &attr) {
+            return;
+        }
+
+        let message = match attr.strip_prefix("on") {
+            Some(event) if spec::event_type(event).is_some() => format!(
+                "use `{} => handler` instead of the `{}` attribute",
+                event, attr
+            ),
+            _ => format!(
+                "unknown attribute `{}` on `<{}>`; use `data-{}` for custom data, \
+                 or a string literal name to skip validation",
+                attr, scope.tag, attr
+            ),
+        };
+        self.error(Error::new_spanned(name, message));
+    }
+}
+
+/// Smaller subtrees are cheaper to create directly than to clone and walk.
+const MIN_TEMPLATE_ELEMENTS: usize = 2;
+
+/// Whether the element's children always come out in the same shape, so
+/// dynamic children can sit at fixed positions in a template. Control flow
+/// that sets attributes or handlers on the element itself rules that out.
+fn is_fixed_shape(element: &Element) -> bool {
+    if element.name.value() == FRAGMENT {
+        return false;
+    }
+    let mut items = Vec::new();
+    flatten_items(&element.items, &mut items);
+    items.iter().all(|item| match item {
+        Item::Child(Child::If(_) | Child::Match(_) | Child::For(_)) => !sets_parent(item),
+        _ => true,
+    })
+}
+
+/// Whether an item, or any control flow branch in it, targets the parent
+/// element rather than adding a child.
+fn sets_parent(item: &Item) -> bool {
+    let any = |items: &[Item]| items.iter().any(sets_parent);
+    match item {
+        Item::Child(Child::If(if_node)) => if_sets_parent(if_node),
+        Item::Child(Child::Match(match_node)) => match_node.arms.iter().any(|arm| any(&arm.body)),
+        Item::Child(Child::For(for_node)) => any(&for_node.body),
+        Item::Child(Child::Element(element)) if element.name.value() == FRAGMENT => {
+            any(&element.items)
+        }
+        Item::Child(_) => false,
+        _ => true,
+    }
+}
+
+fn if_sets_parent(if_node: &If) -> bool {
+    if_node.then_branch.iter().any(sets_parent)
+        || match &if_node.else_branch {
+            Some(Else::If(else_if)) => if_sets_parent(else_if),
+            Some(Else::Block(items)) => items.iter().any(sets_parent),
+            None => false,
+        }
+}
+
+/// Number of elements a template for this element would hold.
+fn static_elements(element: &Element) -> usize {
+    let mut items = Vec::new();
+    flatten_items(&element.items, &mut items);
+    let children = items.iter().map(|item| match item {
+        Item::Child(Child::Element(child)) if is_fixed_shape(child) => static_elements(child),
+        _ => 0,
+    });
+    1 + children.sum::<usize>()
+}
+
+fn flatten_items<'a>(items: &'a [Item], out: &mut Vec<&'a Item>) {
+    for item in items {
+        match item {
+            Item::Child(Child::Element(element)) if element.name.value() == FRAGMENT => {
+                flatten_items(&element.items, out);
+            }
+            _ => out.push(item),
+        }
+    }
+}
+
+/// Literal attribute values can be baked into the template.
+fn is_static_attribute(attribute: &Attribute) -> bool {
+    let is_special = matches!(
+        &attribute.name,
+        AttributeName::Name(name) if name.value() == REF || name.value() == KEY
+    );
+    let is_literal = matches!(
+        &attribute.value,
+        Expr::Lit(expr) if matches!(expr.lit, Lit::Str(_) | Lit::Int(_) | Lit::Bool(_))
+    );
+    !is_special && is_literal
+}
+
+fn is_key(attribute: &Attribute) -> bool {
+    matches!(&attribute.name, AttributeName::Name(name) if name.value() == KEY)
+}
+
+/// Keyed elements are list items: they stay out of their parent's template so
+/// they can be matched up by key.
+fn has_key(element: &Element) -> bool {
+    element.items.iter().any(|item| matches!(item, Item::Attribute(attribute) if is_key(attribute)))
+}
+
+/// Adds a comment marker standing in for a run of dynamic children.
+fn marker<'a>(
+    path: &[usize],
+    index: usize,
+    scope: HoleScope,
+    run: &mut Vec<&'a Item>,
+    holes: &mut Vec<Hole<'a>>,
+) -> TokenStream {
+    let mut path = path.to_vec();
+    path.push(index);
+    holes.push(Hole::Children { path, scope, items: std::mem::take(run) });
+    quote!(::bolt::vdom::StaticNode::Marker)
+}
+
+fn path_tokens(path: &[usize]) -> TokenStream {
+    let path = path.iter().map(|&index| index as u32);
+    quote!(&[#(#path),*])
+}
+
+fn class_toggle(toggle: &ClassToggle, elem: &Ident) -> TokenStream {
+    let name = toggle.name.value();
+    let condition = &toggle.condition;
+    quote_spanned! {condition.span()=>
+        ::bolt::rsx::toggle_class(&mut #elem, #name, #condition);
+    }
+}
+
+fn style_map(style: &Style, elem: &Ident) -> TokenStream {
+    style
+        .declarations
+        .iter()
+        .map(|declaration| {
+            let property = declaration.property.value();
+            let value = &declaration.value;
+            quote_spanned! {value.span()=>
+                ::bolt::rsx::set_style(&mut #elem, #property, &(#value));
+            }
+        })
+        .collect()
+}
+
+fn namespace_tokens(ns: Namespace) -> TokenStream {
+    let variant = match ns {
+        Namespace::Html => format_ident!("Html"),
+        Namespace::Svg => format_ident!("Svg"),
+        Namespace::MathMl => format_ident!("MathMl"),
+    };
+    quote!(::bolt::rsx::Namespace::#variant)
+}
+
+fn child_span(child: &Child) -> Span {
+    match child {
+        Child::Element(element) => element.name.span(),
+        Child::Text(text) => text.span(),
+        Child::TextExpr(expr) | Child::Expr(expr) => expr.span(),
+        Child::Call(call) => call.call.span(),
+        Child::If(if_node) => if_node.condition.to_token_stream().span(),
+        Child::Match(match_node) => match_node.expr.span(),
+        Child::For(for_node) => for_node.pat.span(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(input: &str) -> Vec<String> {
+        let root: Root = syn::parse_str(input).expect("input should parse");
+        let mut generator = Generator::default();
+        generator.root(&root);
+        generator
+            .errors
+            .map(|errors| errors.into_iter().map(|error| error.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn accepts_known_names() {
+        let input = r#"div {
+            class = "a",
+            aria-label = "b",
+            data-id = 1,
+            label { for = "x" },
+            input { type = "checkbox", checked = true },
+            my-widget { anything = 1 },
+            input { ref = input_ref },
+            span { class = ["a", "b"], class:done = true, style = { display: "none", "--accent": color } },
+            svg { viewBox = "0 0 1 1", path { d = "", stroke-width = 2 } },
+            click => |_| ()
+        }"#;
+        assert!(errors(input).is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        let errors = errors(r#"div { colour = "red", widget { }, clack => |_| (), onclick = "" }"#);
+        let has = |message: &str| errors.iter().any(|error| error.contains(message));
+        assert_eq!(errors.len(), 4);
+        assert!(has("unknown attribute `colour` on `<div>`"));
+        assert!(has("unknown HTML element `<widget>`"));
+        assert!(has("unknown event `clack`"));
+        assert!(has("use `click => handler`"));
+    }
+
+    fn expand_str(input: &str) -> String {
+        expand(&syn::parse_str(input).expect("input should parse")).to_string()
+    }
+
+    #[test]
+    fn templates_fixed_shape_subtrees() {
+        let expanded = expand_str(r#"ul { class = "nav", li { click => |_| (), "Home" }, li { @label } }"#);
+        assert!(expanded.contains("RSX_SKELETON"));
+        assert!(expanded.contains("Hole :: element (& [0u32]"));
+        assert!(expanded.contains("Hole :: text (& [1u32 , 0u32]"));
+
+        // Too small to be worth a template
+        assert!(!expand_str(r#"span { @label }"#).contains("RSX_SKELETON"));
+        // Control flow that sets attributes keeps its element out of templates,
+        // but the static children still get one
+        let expanded = expand_str(r#"div { if done { class = "done" }, p { b { "x" } } }"#);
+        assert_eq!(expanded.matches("static RSX_SKELETON").count(), 1);
+
+        // Keyed children are diffed one by one instead of baked into the parent
+        let expanded = expand_str(r#"ul { li { key = 1, "a" }, li { "b" } }"#);
+        assert!(expanded.contains("Hole :: children"));
+    }
+
+    #[test]
+    fn checks_attributes_per_element_and_namespace() {
+        assert_eq!(errors(r#"div { href = "/" }"#).len(), 1);
+        assert!(errors(r#"a { href = "/" }"#).is_empty());
+        assert_eq!(errors(r#"svg { placeholder = "" }"#).len(), 1);
+    }
+
+    #[test]
+    fn rejects_children_of_void_elements() {
+        let errors = errors(r#"div { input { span { } } }"#);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("`<input>` is a void element"));
+    }
+
+    #[test]
+    fn fragments_only_take_children() {
+        assert!(errors(r#"fragment { span { }, "text", div { fragment { p { } } } }"#).is_empty());
+
+        let errors = errors(r#"fragment { class = "a", class:b = true, click => |_| (), span { } }"#);
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].contains("fragments only take children"));
+    }
+
+    #[test]
+    fn passes_blocks_after_calls_as_children() {
+        let expanded = expand_str(r#"div { card(1) { p { "body" }, header: { h2 { "Title" } }, footer: { } } }"#);
+        assert!(expanded.contains("card (1 , :: bolt :: vdom :: Children :: new"));
+        assert!(expanded.contains(". with_slot (\"header\""));
+        assert!(expanded.contains(". with_slot (\"footer\""));
+        assert!(expand_str(r#"ul { self.items(true) { li { } } }"#).contains("items (true , :: bolt :: vdom :: Children"));
+
+        let errors = errors(r#"div { card() { class = "a", header: { }, header: { span { } } } }"#);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("pass attributes and handlers to the component as arguments"));
+        assert!(errors[1].contains("slot `header` is filled twice"));
+    }
+
+    #[test]
+    fn rejects_blocks_after_component() {
+        let errors = errors(r#"div { component::<Card>(props) { p { } } }"#);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("`component` can't take a block"));
+    }
+
+    #[test]
+    fn custom_events_are_string_literals() {
+        assert!(errors(r#"div { touchstart => |_| (), transitionend => |_| (), paste => |_| () }"#).is_empty());
+        assert!(expand_str(r#"my-widget { "value-changed" => |_| () }"#).contains("web_sys :: Event , _ > (& mut"));
+
+        let errors = errors(r#"my-widget { valuechanged => |_| () }"#);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("\"valuechanged\" => handler"));
+    }
+}
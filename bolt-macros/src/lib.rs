@@ -0,0 +1,18 @@
+//! The `rsx!` procedural macro.
+//!
+//! Parses an HTML-like tree, checks tag, attribute and event names against
+//! the HTML, SVG and MathML specs, and expands to calls into the runtime
+//! helpers in `bolt::rsx`. Errors point at the offending tokens.
+
+mod ast;
+mod codegen;
+mod spec;
+
+use proc_macro::TokenStream;
+use syn::parse_macro_input;
+
+#[proc_macro]
+pub fn rsx(input: TokenStream) -> TokenStream {
+    let root = parse_macro_input!(input as ast::Root);
+    codegen::expand(&root).into()
+}
@@ -0,0 +1,334 @@
+//! Element, attribute and event names accepted by `rsx!`, taken from the
+//! HTML Living Standard, SVG 2 and MathML Core.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Namespace {
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    /// Namespace of element `tag` when its parent's children live in `self`.
+    pub fn for_tag(self, tag: &str) -> Namespace {
+        match tag {
+            "svg" => Namespace::Svg,
+            "math" => Namespace::MathMl,
+            _ => self,
+        }
+    }
+
+    /// Namespace of the children of element `tag` created in `self`.
+    pub fn for_children(self, tag: &str) -> Namespace {
+        match (self, tag) {
+            (Namespace::Svg, "foreignObject") => Namespace::Html,
+            _ => self,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Namespace::Html => "HTML",
+            Namespace::Svg => "SVG",
+            Namespace::MathMl => "MathML",
+        }
+    }
+}
+
+pub fn is_custom_element(tag: &str) -> bool {
+    tag.contains('-')
+}
+
+pub fn is_known_element(ns: Namespace, tag: &str) -> bool {
+    match ns {
+        Namespace::Html => HTML_ELEMENTS.contains(&tag),
+        Namespace::Svg => SVG_ELEMENTS.contains(&tag),
+        Namespace::MathMl => MATHML_ELEMENTS.contains(&tag),
+    }
+}
+
+pub fn is_void_element(ns: Namespace, tag: &str) -> bool {
+    ns == Namespace::Html && VOID_ELEMENTS.contains(&tag)
+}
+
+pub fn is_known_attribute(ns: Namespace, tag: &str, attr: &str) -> bool {
+    if attr.starts_with("data-") || attr.starts_with("aria-") || attr == "role" {
+        return true;
+    }
+    match ns {
+        Namespace::Html => {
+            HTML_GLOBAL_ATTRIBUTES.contains(&attr)
+                || HTML_ELEMENT_ATTRIBUTES
+                    .iter()
+                    .any(|(tags, attrs)| tags.contains(&tag) && attrs.contains(&attr))
+        }
+        Namespace::Svg => SVG_ATTRIBUTES.contains(&attr),
+        Namespace::MathMl => MATHML_ATTRIBUTES.contains(&attr),
+    }
+}
+
+/// The `web_sys` type a handler for `event` receives.
+pub fn event_type(event: &str) -> Option<&'static str> {
+    EVENTS
+        .iter()
+        .find(|(names, _)| names.contains(&event))
+        .map(|(_, ty)| *ty)
+}
+
+const EVENTS: &[(&[&str], &str)] = &[
+    (
+        &[
+            "click", "dblclick", "contextmenu", "auxclick", "mousedown", "mouseup", "mousemove",
+            "mouseover", "mouseout", "mouseenter", "mouseleave",
+        ],
+        "MouseEvent",
+    ),
+    (
+        &[
+            "pointerdown", "pointerup", "pointermove", "pointerover", "pointerout",
+            "pointerenter", "pointerleave", "pointercancel", "gotpointercapture",
+            "lostpointercapture",
+        ],
+        "PointerEvent",
+    ),
+    (&["wheel"], "WheelEvent"),
+    (&["touchstart", "touchend", "touchmove", "touchcancel"], "TouchEvent"),
+    (
+        &["drag", "dragstart", "dragend", "dragenter", "dragleave", "dragover", "drop"],
+        "DragEvent",
+    ),
+    (&["keydown", "keyup", "keypress"], "KeyboardEvent"),
+    (&["input", "beforeinput"], "InputEvent"),
+    (&["compositionstart", "compositionupdate", "compositionend"], "CompositionEvent"),
+    (&["copy", "cut", "paste"], "ClipboardEvent"),
+    (&["focus", "blur", "focusin", "focusout"], "FocusEvent"),
+    (&["submit"], "SubmitEvent"),
+    (
+        &["transitionstart", "transitionend", "transitionrun", "transitioncancel"],
+        "TransitionEvent",
+    ),
+    (
+        &["animationstart", "animationend", "animationiteration", "animationcancel"],
+        "AnimationEvent",
+    ),
+    (
+        &[
+            "change", "reset", "select", "scroll", "scrollend", "load", "error", "toggle",
+            "invalid", "close", "cancel", "resize", "beforetoggle", "selectionchange",
+            // Media elements
+            "abort", "canplay", "canplaythrough", "durationchange", "emptied", "ended",
+            "loadeddata", "loadedmetadata", "loadstart", "pause", "play", "playing", "progress",
+            "ratechange", "seeked", "seeking", "stalled", "suspend", "timeupdate",
+            "volumechange", "waiting",
+        ],
+        "Event",
+    ),
+];
+
+const HTML_ELEMENTS: &[&str] = &[
+    "a", "abbr", "address", "area", "article", "aside", "audio", "b", "base", "bdi", "bdo",
+    "blockquote", "body", "br", "button", "canvas", "caption", "cite", "code", "col",
+    "colgroup", "data", "datalist", "dd", "del", "details", "dfn", "dialog", "div", "dl", "dt",
+    "em", "embed", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4",
+    "h5", "h6", "head", "header", "hgroup", "hr", "html", "i", "iframe", "img", "input", "ins",
+    "kbd", "label", "legend", "li", "link", "main", "map", "mark", "menu", "meta", "meter",
+    "nav", "noscript", "object", "ol", "optgroup", "option", "output", "p", "picture", "pre",
+    "progress", "q", "rp", "rt", "ruby", "s", "samp", "script", "search", "section", "select",
+    "slot", "small", "source", "span", "strong", "style", "sub", "summary", "sup", "table",
+    "tbody", "td", "template", "textarea", "tfoot", "th", "thead", "time", "title", "tr",
+    "track", "u", "ul", "var", "video", "wbr",
+];
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source",
+    "track", "wbr",
+];
+
+const HTML_GLOBAL_ATTRIBUTES: &[&str] = &[
+    "accesskey", "autocapitalize", "autocorrect", "autofocus", "class", "contenteditable",
+    "dir", "draggable", "enterkeyhint", "hidden", "id", "inert", "inputmode", "is", "itemid",
+    "itemprop", "itemref", "itemscope", "itemtype", "lang", "nonce", "popover", "slot",
+    "spellcheck", "style", "tabindex", "title", "translate", "writingsuggestions",
+];
+
+const HTML_ELEMENT_ATTRIBUTES: &[(&[&str], &[&str])] = &[
+    (
+        &["a", "area"],
+        &["href", "target", "download", "ping", "rel", "hreflang", "type", "referrerpolicy"],
+    ),
+    (&["area"], &["alt", "coords", "shape"]),
+    (
+        &["audio", "video"],
+        &["src", "crossorigin", "preload", "autoplay", "loop", "muted", "controls"],
+    ),
+    (&["video"], &["poster", "playsinline", "width", "height"]),
+    (&["base"], &["href", "target"]),
+    (&["blockquote", "q", "del", "ins"], &["cite"]),
+    (&["del", "ins", "time"], &["datetime"]),
+    (
+        &["button"],
+        &[
+            "disabled", "form", "formaction", "formenctype", "formmethod", "formnovalidate",
+            "formtarget", "name", "popovertarget", "popovertargetaction", "type", "value",
+            "command", "commandfor",
+        ],
+    ),
+    (&["canvas", "embed", "iframe", "img", "object", "source"], &["width", "height"]),
+    (&["col", "colgroup"], &["span"]),
+    (&["data", "li"], &["value"]),
+    (&["details"], &["name", "open"]),
+    (&["dialog"], &["open"]),
+    (&["embed", "object", "source", "script", "style", "ol"], &["type"]),
+    (&["embed", "iframe", "img", "script", "source", "track", "input"], &["src"]),
+    (&["fieldset"], &["disabled", "form", "name"]),
+    (
+        &["form"],
+        &[
+            "accept-charset", "action", "autocomplete", "enctype", "method", "name", "novalidate",
+            "target", "rel",
+        ],
+    ),
+    (
+        &["iframe"],
+        &["srcdoc", "name", "sandbox", "allow", "allowfullscreen", "referrerpolicy", "loading"],
+    ),
+    (
+        &["img"],
+        &[
+            "alt", "srcset", "sizes", "crossorigin", "usemap", "ismap", "referrerpolicy",
+            "decoding", "loading", "fetchpriority",
+        ],
+    ),
+    (
+        &["input"],
+        &[
+            "accept", "alt", "autocomplete", "checked", "dirname", "disabled", "form",
+            "formaction", "formenctype", "formmethod", "formnovalidate", "formtarget", "height",
+            "list", "max", "maxlength", "min", "minlength", "multiple", "name", "pattern",
+            "placeholder", "popovertarget", "popovertargetaction", "readonly", "required", "size",
+            "step", "type", "value", "width",
+        ],
+    ),
+    (&["label", "output"], &["for"]),
+    (
+        &["link"],
+        &[
+            "href", "crossorigin", "rel", "as", "media", "hreflang", "type", "sizes",
+            "imagesrcset", "imagesizes", "referrerpolicy", "integrity", "blocking", "color",
+            "disabled", "fetchpriority",
+        ],
+    ),
+    (&["map", "object", "output", "slot"], &["name"]),
+    (&["meta"], &["name", "http-equiv", "content", "charset", "media"]),
+    (&["meter"], &["value", "min", "max", "low", "high", "optimum"]),
+    (&["object", "output"], &["form"]),
+    (&["object"], &["data"]),
+    (&["ol"], &["reversed", "start"]),
+    (&["optgroup"], &["disabled", "label"]),
+    (&["option"], &["disabled", "label", "selected", "value"]),
+    (&["progress"], &["value", "max"]),
+    (
+        &["script"],
+        &[
+            "nomodule", "async", "defer", "crossorigin", "integrity", "referrerpolicy", "blocking",
+            "fetchpriority",
+        ],
+    ),
+    (
+        &["select"],
+        &["autocomplete", "disabled", "form", "multiple", "name", "required", "size"],
+    ),
+    (&["source"], &["media", "srcset", "sizes"]),
+    (&["style"], &["media", "blocking"]),
+    (&["td", "th"], &["colspan", "rowspan", "headers"]),
+    (&["th"], &["scope", "abbr"]),
+    (
+        &["template"],
+        &[
+            "shadowrootmode", "shadowrootdelegatesfocus", "shadowrootclonable",
+            "shadowrootserializable",
+        ],
+    ),
+    (
+        &["textarea"],
+        &[
+            "autocomplete", "cols", "dirname", "disabled", "form", "maxlength", "minlength",
+            "name", "placeholder", "readonly", "required", "rows", "wrap",
+        ],
+    ),
+    (&["track"], &["default", "kind", "label", "srclang"]),
+    (&["html"], &["xmlns"]),
+];
+
+const SVG_ELEMENTS: &[&str] = &[
+    "a", "animate", "animateMotion", "animateTransform", "circle", "clipPath", "defs", "desc",
+    "ellipse", "feBlend", "feColorMatrix", "feComponentTransfer", "feComposite",
+    "feConvolveMatrix", "feDiffuseLighting", "feDisplacementMap", "feDistantLight",
+    "feDropShadow", "feFlood", "feFuncA", "feFuncB", "feFuncG", "feFuncR", "feGaussianBlur",
+    "feImage", "feMerge", "feMergeNode", "feMorphology", "feOffset", "fePointLight",
+    "feSpecularLighting", "feSpotLight", "feTile", "feTurbulence", "filter", "foreignObject",
+    "g", "image", "line", "linearGradient", "marker", "mask", "metadata", "mpath", "path",
+    "pattern", "polygon", "polyline", "radialGradient", "rect", "script", "set", "stop",
+    "style", "svg", "switch", "symbol", "text", "textPath", "title", "tspan", "use", "view",
+];
+
+const SVG_ATTRIBUTES: &[&str] = &[
+    // Core and styling
+    "id", "class", "style", "lang", "tabindex", "xmlns", "autofocus",
+    // Geometry
+    "cx", "cy", "r", "rx", "ry", "x", "y", "x1", "x2", "y1", "y2", "width", "height", "d",
+    "points", "pathLength", "viewBox", "preserveAspectRatio", "transform", "transform-origin",
+    "href",
+    // Presentation
+    "alignment-baseline", "baseline-shift", "clip", "clip-path", "clip-rule", "color",
+    "color-interpolation", "color-interpolation-filters", "color-rendering", "cursor",
+    "direction", "display", "dominant-baseline", "fill", "fill-opacity", "fill-rule", "filter",
+    "flood-color", "flood-opacity", "font-family", "font-size", "font-size-adjust",
+    "font-stretch", "font-style", "font-variant", "font-weight", "image-rendering",
+    "letter-spacing", "lighting-color", "marker-end", "marker-mid", "marker-start", "mask",
+    "mask-type", "opacity", "overflow", "paint-order", "pointer-events", "shape-rendering",
+    "stop-color", "stop-opacity", "stroke", "stroke-dasharray", "stroke-dashoffset",
+    "stroke-linecap", "stroke-linejoin", "stroke-miterlimit", "stroke-opacity", "stroke-width",
+    "text-anchor", "text-decoration", "text-rendering", "unicode-bidi", "vector-effect",
+    "visibility", "word-spacing", "writing-mode",
+    // Gradients, patterns, markers, clipping and masking
+    "gradientUnits", "gradientTransform", "spreadMethod", "fx", "fy", "fr", "offset",
+    "patternUnits", "patternContentUnits", "patternTransform", "markerWidth", "markerHeight",
+    "markerUnits", "refX", "refY", "orient", "clipPathUnits", "maskUnits", "maskContentUnits",
+    // Text
+    "dx", "dy", "rotate", "textLength", "lengthAdjust", "startOffset", "method", "spacing",
+    "side",
+    // Filters
+    "filterUnits", "primitiveUnits", "in", "in2", "result", "stdDeviation", "mode", "type",
+    "values", "operator", "k1", "k2", "k3", "k4", "scale", "xChannelSelector",
+    "yChannelSelector", "radius", "order", "kernelMatrix", "divisor", "bias", "targetX",
+    "targetY", "edgeMode", "kernelUnitLength", "preserveAlpha", "surfaceScale",
+    "diffuseConstant", "specularConstant", "specularExponent", "azimuth", "elevation", "z",
+    "pointsAtX", "pointsAtY", "pointsAtZ", "limitingConeAngle", "baseFrequency", "numOctaves",
+    "seed", "stitchTiles", "tableValues", "slope", "intercept", "amplitude", "exponent",
+    "crossorigin",
+    // Animation
+    "attributeName", "begin", "dur", "end", "min", "max", "restart", "repeatCount",
+    "repeatDur", "calcMode", "keyTimes", "keySplines", "from", "to", "by", "additive",
+    "accumulate", "path", "keyPoints",
+    // Links and conditional processing
+    "target", "download", "ping", "rel", "hreflang", "referrerpolicy", "requiredExtensions",
+    "systemLanguage", "media",
+];
+
+const MATHML_ELEMENTS: &[&str] = &[
+    "math", "annotation", "annotation-xml", "maction", "menclose", "merror", "mfrac", "mi",
+    "mmultiscripts", "mn", "mo", "mover", "mpadded", "mphantom", "mprescripts", "mroot", "mrow",
+    "ms", "mspace", "msqrt", "mstyle", "msub", "msubsup", "msup", "mtable", "mtd", "mtext",
+    "mtr", "munder", "munderover", "semantics",
+];
+
+const MATHML_ATTRIBUTES: &[&str] = &[
+    "id", "class", "style", "tabindex", "xmlns", "autofocus", "dir", "display", "displaystyle",
+    "mathbackground", "mathcolor", "mathsize", "mathvariant", "scriptlevel", "href", "accent",
+    "accentunder", "align", "columnalign", "columnlines", "columnspacing", "columnspan",
+    "rowalign", "rowlines", "rowspacing", "rowspan", "fence", "form", "largeop",
+    "linethickness", "lspace", "rspace", "maxsize", "minsize", "movablelimits", "notation",
+    "separator", "stretchy", "symmetric", "voffset", "width", "height", "depth", "encoding",
+    "actiontype", "selection", "intent", "arg",
+];
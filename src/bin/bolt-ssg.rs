@@ -0,0 +1,48 @@
+// Pre-renders every route of the app into static pages:
+//
+//     cargo run --bin bolt-ssg -- [--template index.html] [--content content.json] [--out dist]
+//
+// Each page is the template with the route's #app as its body, which the
+// wasm build hydrates on load.
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+fn main() {
+    let mut template = PathBuf::from("index.html");
+    let mut content = PathBuf::from("content.json");
+    let mut out_dir = PathBuf::from("dist");
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--template" => &mut template,
+            "--content" => &mut content,
+            "--out" => &mut out_dir,
+            _ => {
+                eprintln!("usage: bolt-ssg [--template index.html] [--content content.json] [--out dist]");
+                process::exit(2);
+            }
+        };
+        match args.next() {
+            Some(value) => *target = value.into(),
+            None => {
+                eprintln!("bolt-ssg: {} needs a value", arg);
+                process::exit(2);
+            }
+        }
+    }
+
+    match bolt::ssg::generate(&template, &content, &out_dir) {
+        Ok(pages) => {
+            for page in pages {
+                println!("{}", page.display());
+            }
+        }
+        Err(error) => {
+            eprintln!("bolt-ssg: {}", error);
+            process::exit(1);
+        }
+    }
+}
@@ -1,11 +1,8 @@
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use web_sys::Element;
+use crate::vdom::VNode;
 use crate::rsx;
-use crate::rsx_internal;
 use crate::state::actions::{Action, Operation};
 
-pub fn counter_actions() -> Element {
+pub fn counter_actions() -> VNode {
     rsx!(div {
         class = "counter-actions",
         button {
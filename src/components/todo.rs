@@ -1,10 +1,7 @@
-use wasm_bindgen::JsCast;
-use wasm_bindgen::prelude::*;
-use web_sys::{Element, console, HtmlElement, HtmlInputElement};
-use crate::rsx;
-use crate::rsx_internal;
+use web_sys::{console, HtmlElement, HtmlInputElement};
+use crate::{rsx, NodeRef};
+use crate::vdom::{component, Component, VNode};
 use crate::state::{STATE, actions::{Action, TodoOperation}};
-use crate::utils::get_document;
 
 // Add a capacity hint for better Vec performance
 pub fn add_todo(text: String) {
@@ -13,7 +10,10 @@ pub fn add_todo(text: String) {
         if state.todos.capacity() == state.todos.len() {
             state.todos.reserve(32); // Reserve in chunks to avoid frequent reallocations
         }
+        let id = state.next_todo_id;
+        state.next_todo_id += 1;
         state.todos.push(crate::state::app_state::Todo {
+            id,
             text,
             completed: false,
         });
@@ -21,11 +21,8 @@ pub fn add_todo(text: String) {
     });
 }
 
-pub fn handle_add_todo(event: Option<web_sys::Event>) {
-    if let Some(input) = get_document()
-        .get_element_by_id("todo-input")
-        .and_then(|el| el.dyn_into::<HtmlInputElement>().ok()) 
-    {
+pub fn handle_add_todo(input_ref: &NodeRef<HtmlInputElement>) {
+    if let Some(input) = input_ref.get() {
         let text = input.value();
         if !text.is_empty() {
             add_todo(text);
@@ -34,40 +31,71 @@ pub fn handle_add_todo(event: Option<web_sys::Event>) {
     }
 }
 
-pub fn todo_input() -> Element {
-    rsx!(div {
-        class = "input-container",
-        input {
-            id = "todo-input",
-            class = "input-field",
-            type = "text",
-            placeholder = "Add new todo",
-            keydown => |e: web_sys::Event| {
-                if let Ok(key_event) = e.dyn_into::<web_sys::KeyboardEvent>() {
-                    if key_event.key() == "Enter" {
-                        handle_add_todo(None);
+// Input and button for new todos. The input keeps its NodeRef across
+// renders and takes focus when the list is shown.
+struct TodoInput {
+    input_ref: NodeRef<HtmlInputElement>,
+}
+
+impl Component for TodoInput {
+    type Props = ();
+
+    fn create(_props: &()) -> Self {
+        Self { input_ref: NodeRef::new() }
+    }
+
+    fn render(&self, _props: &()) -> VNode {
+        let keydown_ref = self.input_ref.clone();
+        let click_ref = self.input_ref.clone();
+
+        rsx!(div {
+            class = "input-container",
+            input {
+                ref = self.input_ref,
+                id = "todo-input",
+                class = "input-field",
+                type = "text",
+                placeholder = "Add new todo",
+                aria-label = "New todo",
+                keydown => move |e: web_sys::KeyboardEvent| {
+                    if e.key() == "Enter" {
+                        handle_add_todo(&keydown_ref);
                     }
                 }
+            },
+            button {
+                "Add Todo",
+                class = "btn",
+                click => move |_| handle_add_todo(&click_ref)
             }
-        },
-        button {
-            "Add Todo",
-            class = "btn",
-            click => |_| handle_add_todo(None)
+        })
+    }
+
+    fn mounted(&mut self, _props: &()) {
+        if let Some(input) = self.input_ref.get() {
+            let _ = input.focus();
         }
-    })
+    }
+}
+
+pub fn todo_input() -> VNode {
+    component::<TodoInput>(())
 }
 
-fn render_todo_item(index: usize, todo: &crate::state::app_state::Todo) -> Element {
+fn render_todo_item(index: usize, todo: &crate::state::app_state::Todo) -> VNode {
     let toggle_action = Action::Todo(TodoOperation::Toggle(index));
     let remove_action = Action::Todo(TodoOperation::Remove(index));
     
     rsx!(div {
-        class = if todo.completed { "todo-item completed" } else { "todo-item" },
+        key = todo.id,
+        class = "todo-item",
+        class:completed = todo.completed,
+        data-index = index,
         input {
             class = "todo-checkbox",
             type = "checkbox",
-            checked = todo.completed,
+            aria-label = "Mark todo as completed",
+            .checked = todo.completed,
             click => crate::action_handler(toggle_action)
         },
         span { 
@@ -76,13 +104,14 @@ fn render_todo_item(index: usize, todo: &crate::state::app_state::Todo) -> Eleme
         },
         button {
             class = "todo-delete",
+            aria-label = "Delete todo",
             "Delete",
             click => crate::action_handler(remove_action)
         }
     })
 }
 
-pub fn todo_list() -> Element {
+pub fn todo_list() -> VNode {
     STATE.with(|state| {
         let state = state.borrow();
         rsx!(div {
@@ -90,7 +119,9 @@ pub fn todo_list() -> Element {
             todo_input(),
             div {
                 class = "todos",
-                state.todos.iter().enumerate(), => |index, todo| render_todo_item(index, todo)
+                for (index, todo) in state.todos.iter().enumerate() {
+                    render_todo_item(index, todo)
+                }
             }
         })
     })
@@ -102,32 +133,16 @@ mod tests {
     use wasm_bindgen_test::*;
     use web_sys::{window, HtmlElement};
     use crate::state::app_state::Todo;
+    use crate::vdom;
 
     wasm_bindgen_test_configure!(run_in_browser);
 
-    fn setup_todo_input() -> HtmlInputElement {
-        let doc = get_document();
-        let input = doc.create_element("input")
-            .expect("should create input")
-            .dyn_into::<HtmlInputElement>()
-            .expect("should be input element");
-        
-        input.set_id("todo-input");
-        doc.body()
-            .expect("should have body")
-            .append_child(&input)
-            .expect("should append input");
-        
-        input
-    }
+    fn setup_todo_input() -> (NodeRef<HtmlInputElement>, HtmlInputElement) {
+        let input_ref = NodeRef::new();
+        vdom::mount(rsx!(input { ref = input_ref, type = "text" }));
+        let input = input_ref.get().expect("should bind input");
 
-    fn cleanup_todo_input() {
-        if let Some(input) = get_document().get_element_by_id("todo-input") {
-            input.parent_element()
-                .expect("should have parent")
-                .remove_child(&input)
-                .expect("should remove input");
-        }
+        (input_ref, input)
     }
 
     #[wasm_bindgen_test]
@@ -152,7 +167,7 @@ mod tests {
     #[wasm_bindgen_test]
     fn test_handle_add_todo() {
         // Setup
-        let input = setup_todo_input();
+        let (input_ref, input) = setup_todo_input();
         STATE.with(|state| {
             let mut state = state.borrow_mut();
             state.todos.clear();
@@ -160,7 +175,7 @@ mod tests {
 
         // Test adding todo via handler
         input.set_value("Test todo via handler");
-        handle_add_todo(None);
+        handle_add_todo(&input_ref);
 
         STATE.with(|state| {
             let state = state.borrow();
@@ -168,9 +183,6 @@ mod tests {
             assert_eq!(state.todos[0].text, "Test todo via handler");
             assert_eq!(input.value(), ""); // Input should be cleared
         });
-
-        // Cleanup
-        cleanup_todo_input();
     }
 
     #[wasm_bindgen_test]
@@ -180,13 +192,14 @@ mod tests {
             let mut state = state.borrow_mut();
             state.todos.clear();
             state.todos.push(Todo {
+                id: 0,
                 text: "Test todo".to_string(),
                 completed: false,
             });
         });
 
         // Test rendering
-        let todo_list_element = todo_list();
+        let todo_list_element = vdom::mount(todo_list());
         assert!(todo_list_element.query_selector(".todo-item").unwrap().is_some());
         
         // Test todo item content
@@ -1,14 +1,17 @@
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use web_sys::Element;
+use crate::vdom::VNode;
 use crate::rsx;
-use crate::rsx_internal;
 use crate::state::actions::Action;
+use crate::tooltip::{tooltip, TooltipPosition};
 
-pub fn toggle_theme_button() -> Element {
-    rsx!(button {
-        class = "btn btn-primary",
-        "Toggle Theme",
-        click => crate::action_handler(Action::ToggleTheme)
+pub fn toggle_theme_button() -> VNode {
+    rsx!(fragment {
+        tooltip(TooltipPosition::Top) {
+            button {
+                class = "btn btn-primary",
+                "Toggle Theme",
+                click => crate::action_handler(Action::ToggleTheme)
+            },
+            content: { "Switch between the light and dark theme" }
+        }
     })
 }
\ No newline at end of file
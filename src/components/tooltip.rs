@@ -1,8 +1,13 @@
-use web_sys::Element;
-use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use crate::node_ref::NodeRef;
+use crate::vdom::{portal, Children, VNode};
 use crate::rsx;
-use crate::rsx_internal;
 
+// Space between the trigger and the bubble, where the arrow goes
+const GAP: f64 = 5.0;
+
+#[derive(Clone, Copy)]
+#[allow(dead_code, reason = "every side is available to callers; the app itself only uses some")]
 pub enum TooltipPosition {
     Top,
     Bottom,
@@ -11,7 +16,7 @@ pub enum TooltipPosition {
 }
 
 impl TooltipPosition {
-    fn to_class(&self) -> &'static str {
+    fn to_class(self) -> &'static str {
         match self {
             TooltipPosition::Top => "tooltip-top",
             TooltipPosition::Bottom => "tooltip-bottom",
@@ -19,17 +24,118 @@ impl TooltipPosition {
             TooltipPosition::Right => "tooltip-right",
         }
     }
+
+    // Where `bubble` goes on this side of `trigger`, in viewport coordinates
+    fn place(&self, trigger: &HtmlElement, bubble: &HtmlElement) -> (f64, f64) {
+        let rect = trigger.get_bounding_client_rect();
+        let (width, height) = (bubble.offset_width() as f64, bubble.offset_height() as f64);
+        let centered_x = rect.left() + (rect.width() - width) / 2.0;
+        let centered_y = rect.top() + (rect.height() - height) / 2.0;
+        match self {
+            TooltipPosition::Top => (centered_x, rect.top() - height - GAP),
+            TooltipPosition::Bottom => (centered_x, rect.bottom() + GAP),
+            TooltipPosition::Left => (rect.left() - width - GAP, centered_y),
+            TooltipPosition::Right => (rect.right() + GAP, centered_y),
+        }
+    }
 }
 
-pub fn tooltip(content: &str, position: TooltipPosition, children: Element) -> Element {
-    let position_class = position.to_class();
-    
+/// Shows the `content` slot next to the children while they're hovered or
+/// focused:
+///
+/// ```ignore
+/// tooltip(TooltipPosition::Top) {
+///     button { "Save" },
+///     content: { b { "Ctrl+S" }, " saves the draft" }
+/// }
+/// ```
+///
+/// The bubble is rendered at the end of `<body>` through a portal, so
+/// `overflow: hidden` or a stacking context around the children can't clip
+/// it, and is placed next to them when it shows.
+pub fn tooltip(position: TooltipPosition, mut children: Children) -> VNode {
+    let content = children.slot("content");
+    let trigger = NodeRef::<HtmlElement>::new();
+    let bubble = NodeRef::<HtmlElement>::new();
     rsx!(div {
         class = "tooltip-container",
+        ref = trigger,
+        mouseenter => show(position, &trigger, &bubble, true),
+        mouseleave => show(position, &trigger, &bubble, false),
+        focusin => show(position, &trigger, &bubble, true),
+        focusout => show(position, &trigger, &bubble, false),
         children,
-        span {
-            class = format!("tooltip {}", position_class),
-            @content
-        }
+        portal("body", rsx!(span {
+            class = ["tooltip", position.to_class()],
+            ref = bubble,
+            content
+        }))
     })
-}
\ No newline at end of file
+}
+
+// A handler that places the bubble next to the trigger and shows it, or
+// hides it
+fn show<E>(
+    position: TooltipPosition,
+    trigger: &NodeRef<HtmlElement>,
+    bubble: &NodeRef<HtmlElement>,
+    visible: bool,
+) -> impl Fn(E) + 'static {
+    let (trigger, bubble) = (trigger.clone(), bubble.clone());
+    move |_| {
+        let (Some(trigger), Some(bubble)) = (trigger.get(), bubble.get()) else {
+            return;
+        };
+        if !visible {
+            let _ = bubble.class_list().remove_1("tooltip-visible");
+            return;
+        }
+        let (left, top) = position.place(&trigger, &bubble);
+        let style = bubble.style();
+        let _ = style.set_property("left", &format!("{}px", left));
+        let _ = style.set_property("top", &format!("{}px", top));
+        let _ = bubble.class_list().add_1("tooltip-visible");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vdom;
+    use crate::utils::get_document;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::*;
+    use web_sys::MouseEvent;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_content_shows_in_the_body_while_hovered() {
+        let container = vdom::mount(rsx!(div {
+            style = { overflow: "hidden" },
+            tooltip(TooltipPosition::Left) {
+                content: { b { "Ctrl+S" } },
+                button { "Save" }
+            }
+        }));
+        let trigger = container.query_selector(".tooltip-container").unwrap().unwrap();
+        let bubble = get_document().query_selector(".tooltip-left").unwrap().unwrap();
+        let hover = |event: &str| {
+            trigger.dispatch_event(&MouseEvent::new(event).unwrap()).unwrap();
+        };
+
+        assert!(container.query_selector(".tooltip").unwrap().is_none());
+        assert_eq!(bubble.text_content().unwrap(), "Ctrl+S");
+        assert!(!bubble.class_list().contains("tooltip-visible"));
+
+        hover("mouseenter");
+        assert!(bubble.class_list().contains("tooltip-visible"));
+        assert!(bubble.unchecked_ref::<HtmlElement>().style().get_property_value("left").unwrap().ends_with("px"));
+
+        hover("mouseleave");
+        assert!(!bubble.class_list().contains("tooltip-visible"));
+
+        vdom::unmount(&container);
+        assert!(get_document().query_selector(".tooltip-left").unwrap().is_none());
+    }
+}
@@ -1,11 +1,8 @@
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use web_sys::Element;
+use crate::vdom::VNode;
 use crate::rsx;
-use crate::rsx_internal;
 use crate::state::actions::Action;
 
-pub fn toggle_visibility() -> Element {
+pub fn toggle_visibility() -> VNode {
     rsx!(button {
         class = "btn btn-primary",
         "Toggle Visibility",
@@ -2,6 +2,8 @@ use wasm_bindgen::prelude::*;
 use web_sys::XmlHttpRequest;
 use js_sys::{JSON, Object, Reflect, Array};
 use std::cell::RefCell;
+use std::rc::Rc;
+use crate::vdom::use_context;
 
 thread_local! {
     static CONTENT_CACHE: RefCell<Option<Content>> = RefCell::new(None);
@@ -68,53 +70,93 @@ fn parse_json(json_str: &str) -> Option<Content> {
     Some(Content { articles })
 }
 
-fn create_fallback_article() -> Article {
-    Article {
-        title: "Loading...".into(),
-        content: "Content is loading...".into(),
-        date: String::new(),
-        author: String::new(),
-        tags: Vec::new(),
+// content.json read natively, e.g. by `bolt-ssg`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_content(json_str: &str) -> Option<Content> {
+    let parsed: serde_json::Value = serde_json::from_str(json_str).ok()?;
+    let string = |article: &serde_json::Value, key: &str| {
+        article.get(key).and_then(|value| value.as_str()).unwrap_or_default().to_string()
+    };
+
+    let articles = parsed
+        .get("articles")?
+        .as_array()?
+        .iter()
+        .filter(|article| article.is_object())
+        .map(|article| Article {
+            title: string(article, "title"),
+            content: string(article, "content"),
+            date: string(article, "date"),
+            author: string(article, "author"),
+            tags: article
+                .get("tags")
+                .and_then(|tags| tags.as_array())
+                .map(|tags| tags.iter().filter_map(|tag| tag.as_str()).map(String::from).collect())
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    Some(Content { articles })
+}
+
+/// Where the articles come from. The default one fetches content.json
+/// through `load_content`; pre-rendering and tests provide fixed content.
+#[derive(Clone)]
+pub struct ContentLoader {
+    load: Rc<dyn Fn() -> Result<Content, JsValue>>,
+}
+
+impl ContentLoader {
+    pub fn new(load: impl Fn() -> Result<Content, JsValue> + 'static) -> Self {
+        Self { load: Rc::new(load) }
+    }
+
+    pub fn fixed(content: Content) -> Self {
+        Self::new(move || Ok(content.clone()))
+    }
+
+    pub fn load(&self) -> Result<Content, JsValue> {
+        (self.load)()
     }
 }
 
-pub fn load_content() -> Content {
+impl Default for ContentLoader {
+    fn default() -> Self {
+        Self::new(load_content)
+    }
+}
+
+/// The provided `ContentLoader`, or one that fetches content.json.
+pub fn use_content_loader() -> ContentLoader {
+    use_context().unwrap_or_default()
+}
+
+// Fetches content.json, or reports why it couldn't. Only successes are
+// cached, so calling it again after a failure retries.
+pub fn load_content() -> Result<Content, JsValue> {
 
     // Check if content is already cached
     if let Some(cached_content) = CONTENT_CACHE.with(|cache| cache.borrow().clone()) {
-        return cached_content;
+        return Ok(cached_content);
     }
 
-    // Create fallback content in case of loading failure
-    let fallback = Content {
-        articles: vec![create_fallback_article()]
-    };
-
-    let xhr = XmlHttpRequest::new().unwrap();
-    xhr.open_with_async("GET", "content.json", false).unwrap();
-    xhr.send().unwrap();
-
+    let xhr = XmlHttpRequest::new()?;
+    // From the site root, so pages under /articles/ find it too
+    xhr.open_with_async("GET", "/content.json", false)?;
+    xhr.send()?;
 
     // Check if request was successful (status 200)
-    let content = if xhr.status().unwrap() == 200 {
-        if let Ok(response_text) = xhr.response_text() {
-            if let Some(text) = response_text {
-                parse_json(&text).unwrap_or(fallback.clone())
-            } else {
-                fallback.clone()
-            }
-        } else {
-            fallback.clone()
-        }
-    } else {
-        fallback.clone()
-    };
+    let status = xhr.status()?;
+    if status != 200 {
+        return Err(JsValue::from_str(&format!("content.json responded with HTTP {}", status)));
+    }
+    let text = xhr.response_text()?.unwrap_or_default();
+    let content = parse_json(&text).ok_or_else(|| JsValue::from_str("content.json is not valid content"))?;
 
     // Cache the content
     CONTENT_CACHE.with(|cache| {
         *cache.borrow_mut() = Some(content.clone());
     });
 
-
-    content
-}
\ No newline at end of file
+    Ok(content)
+}
@@ -0,0 +1,138 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event};
+
+thread_local! {
+    static LIVE_LISTENERS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Type-erased event handler. `handler` builds one from a closure taking the
+/// concrete event type.
+pub type Handler = Box<dyn FnMut(Event)>;
+
+pub fn handler<E, F>(mut handler: F) -> Handler
+where
+    E: JsCast + 'static,
+    F: FnMut(E) + 'static,
+{
+    Box::new(move |event: Event| handler(event.unchecked_into()))
+}
+
+/// An event listener attached to an element. Dropping it removes the
+/// listener and frees its closure.
+pub struct EventListener {
+    target: Element,
+    event: &'static str,
+    handler: Rc<RefCell<Option<Handler>>>,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+impl EventListener {
+    pub fn new(target: &Element, event: &'static str, handler: Handler) -> Self {
+        let handler = Rc::new(RefCell::new(Some(handler)));
+        let slot = handler.clone();
+        // The handler is taken out while it runs, so it may replace itself
+        // (a click that re-renders its own button) without a double borrow
+        let closure = Closure::wrap(Box::new(move |event: Event| {
+            let current = slot.borrow_mut().take();
+            if let Some(mut current) = current {
+                current(event);
+                let mut slot = slot.borrow_mut();
+                if slot.is_none() {
+                    *slot = Some(current);
+                }
+            }
+        }) as Box<dyn FnMut(Event)>);
+        target
+            .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+            .unwrap();
+        LIVE_LISTENERS.with(|live| live.set(live.get() + 1));
+
+        Self { target: target.clone(), event, handler, closure }
+    }
+
+    pub fn event(&self) -> &'static str {
+        self.event
+    }
+
+    /// Swaps the handler without touching the DOM listener.
+    pub fn set_handler(&self, handler: Handler) {
+        *self.handler.borrow_mut() = Some(handler);
+    }
+}
+
+impl Drop for EventListener {
+    fn drop(&mut self) {
+        // A closure dropped while it is running (a click handler that triggers
+        // a re-render) is freed by wasm-bindgen once it returns.
+        let _ = self
+            .target
+            .remove_event_listener_with_callback(self.event, self.closure.as_ref().unchecked_ref());
+        LIVE_LISTENERS.with(|live| live.set(live.get() - 1));
+    }
+}
+
+/// Number of listeners currently attached through `rsx!`.
+pub fn live_listeners() -> usize {
+    LIVE_LISTENERS.with(Cell::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsx;
+    use crate::utils::get_document;
+    use crate::vdom;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn memory_bytes() -> u32 {
+        wasm_bindgen::memory()
+            .unchecked_into::<js_sys::WebAssembly::Memory>()
+            .buffer()
+            .unchecked_into::<js_sys::ArrayBuffer>()
+            .byte_length()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_replaced_subtrees_drop_their_listeners() {
+        let container = vdom::mount(rsx!(button { click => |_| () }));
+        let live = live_listeners();
+
+        vdom::render(&container, rsx!(span { "no listeners" }));
+
+        assert_eq!(live_listeners(), live - 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memory_stays_flat_across_renders() {
+        let host = vdom::mount(crate::APP.with(|app_ref| rsx!(div { id = "app", ref = app_ref })));
+        get_document().body().unwrap().append_child(&host).unwrap();
+
+        // Let the allocator settle before measuring
+        for _ in 0..100 {
+            crate::render();
+        }
+        let listeners = live_listeners();
+        let memory = memory_bytes();
+
+        for _ in 0..1000 {
+            crate::render();
+        }
+
+        assert!(listeners > 0);
+        assert_eq!(live_listeners(), listeners);
+        assert!(
+            memory_bytes() <= memory + 64 * 1024,
+            "wasm memory grew from {} to {} bytes over 1000 renders",
+            memory,
+            memory_bytes()
+        );
+
+        vdom::unmount(&host);
+        host.remove();
+    }
+}
@@ -1,126 +1,141 @@
+// Lets `rsx!` output refer to this crate as `::bolt` from inside it too
+extern crate self as bolt;
+
 mod macros;
+pub mod events;
 mod components;
 mod state;
 mod theme;
 mod utils;
-mod rsx;
+pub mod rsx;
 mod content_loader;
 mod router;
 mod performance;
+pub mod node_ref;
+pub mod vdom;
+pub mod signals;
+pub mod scheduler;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ssg;
 
 use performance::{measure, log_stats};
-use router::{Route, get_current_route, navigate_to};
+use router::{Route, Router, get_current_route, use_router};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{Element,HtmlElement};
+use web_sys::Element;
 use components::*;
 use state::{STATE, dispatch, app_state::AppState};
-use theme::Theme;
+use theme::{ThemeProvider, use_theme};
 // use components::styled_button::{styled_button};
 use crate::utils::get_document;
 use crate::todo::todo_list;
 use crate::counter::counter_actions;
 use crate::toggle_theme::toggle_theme_button;
 use crate::visibility::toggle_visibility;
-use content_loader::{Content, load_content};
+use content_loader::{Content, ContentLoader, use_content_loader};
+use vdom::VNode;
+
+pub use bolt_macros::rsx;
+pub use node_ref::NodeRef;
+pub use signals::{Effect, Memo, Signal};
+pub use vdom::{
+    component, error_boundary, portal, provide_context, use_context, use_memo, use_reducer, use_state,
+    with_root_context, Children, Component,
+};
+
+thread_local! {
+    // The #app element main() mounts into
+    static APP: NodeRef<Element> = NodeRef::default();
+}
 
-// Generic event handler that can handle both mouse and keyboard events
-pub fn action_handler(action: state::actions::Action) -> impl FnMut(web_sys::Event) {
+// Generic event handler that dispatches an action for any event type
+pub fn action_handler<E>(action: state::actions::Action) -> impl FnMut(E) {
     move |_| dispatch(action)
 }
 
 // Rendering
 fn render() {
-    STATE.with(|state| {
-        let state = state.borrow();
-
-        if let Some(app) = get_document().get_element_by_id("app") {
-            // Update content based on route
-            let new_content = render_route_content(&state.current_route);
-            app.set_inner_html("");
-            append_child!(app, &render_nav());
-            append_child!(app, &new_content);
-            append_child!(app, &toggle_visibility());
-            append_child!(app, &counter_actions());
-            append_child!(app, &toggle_theme_button());
-        }
-        
-        // Update counter
-        if let Some(elem) = get_document().get_element_by_id("count") {
-            let mut buffer = itoa::Buffer::new();
-            elem.set_text_content(Some(buffer.format(state.counter)));
-        }
+    let Some(app) = APP.with(NodeRef::get) else {
+        return;
+    };
+    // Not borrowed while patching: lifecycle hooks and handlers that fire
+    // meanwhile may dispatch
+    let route = STATE.with(|state| state.borrow().current_route.clone());
 
-        // Update theme
-        if let Some(root) = get_document().get_element_by_id("app") {
-            root.set_class_name(&state.theme.to_str());
-        }
-
-        // Update visibility
-        if let Some(content) = get_document().get_element_by_id("content") {
-            let new_content = content.dyn_into::<HtmlElement>().unwrap_throw();
-            new_content.style()
-                .set_property("display", match state.visibility {
-                    state::app_state::Visibility::Shown => "block",
-                    state::app_state::Visibility::Hidden => "none",
-                })
-                .unwrap_throw();
-        }
-
-        // Update todo list
-        if let Some(container) = get_document().get_element_by_id("todo-container") {
-            let new_todo_list = todo_list();
-            container.set_inner_html("");
-            append_child!(container, new_todo_list);
-        }
-    });
+    // Patch the content for the current route into the previous render.
+    // Counter, theme and visibility are bound to their signals instead
+    vdom::render(&app, render_app_content(&route));
 }
 
-fn render_articles(content: Content) -> Element {
+fn render_articles(content: Content) -> VNode {
     rsx!(
         section {
             id = "articles",
             class = "articles-section",
-            content.articles.iter().enumerate(), => |i, article| rsx!(div {
-                class = "article-card",
-                h2 { @&article.title },
-                div {
-                    class = "article-meta",
-                    span { @&article.date },
-                    span { @&article.author }
-                },
+            for article in &content.articles {
                 div {
-                    class = "article-tags",
-                    article.tags.iter().enumerate(), => |i, tag| rsx!(span {
-                        class = "tag",
-                        @tag
-                    })
-                },
-                p { @&article.content }
-            })
-        })
+                    key = article.title,
+                    class = "article-card",
+                    h2 { @&article.title },
+                    div {
+                        class = "article-meta",
+                        if !article.date.is_empty() {
+                            span { @&article.date }
+                        },
+                        if !article.author.is_empty() {
+                            span { @&article.author }
+                        }
+                    },
+                    if !article.tags.is_empty() {
+                        div {
+                            class = "article-tags",
+                            for tag in &article.tags {
+                                span {
+                                    class = "tag",
+                                    @tag
+                                }
+                            }
+                        }
+                    },
+                    p { @&article.content }
+                }
+            }
+        }
+    )
 }
 
 // Add route-specific content rendering
-fn render_route_content(route: &Route) -> Element {
+fn render_route_content(route: &Route) -> VNode {
     // measure("route_render", || {
     match route {
-        Route::Home => rsx!(div {
-            id = "content",
-            "Counter: ",
-            span { 
-                id = "count",
-                class = "counter",
-                "0"
-            },
-            div {
-                id = "todo-container",
-                todo_list()
-            }
-        }),
-        Route::Articles => {
-            render_articles(load_content())
+        Route::Home => {
+            let (visibility, counter) = STATE.with(|state| {
+                let state = state.borrow();
+                (state.visibility.clone(), state.counter.clone())
+            });
+            rsx!(div {
+                id = "content",
+                style = { display: visibility },
+                "Counter: ",
+                span { 
+                    id = "count",
+                    class = "counter",
+                    @counter
+                },
+                div {
+                    id = "todo-container",
+                    todo_list()
+                }
+            })
         },
+        Route::Articles => error_boundary(
+            || Ok(render_articles(use_content_loader().load()?)),
+            |error, retry| rsx!(div {
+                class = "error-panel",
+                p { "Couldn't load the articles: ", @error },
+                button { class = "btn", click => move |_| retry.retry(), "Retry" }
+            })
+        ),
         Route::About => rsx!(div {
             class = "about-page",
             h1 { "About Us" },
@@ -136,83 +151,131 @@ fn render_route_content(route: &Route) -> Element {
 }
 
 // Add navigation menu
-fn render_nav() -> Element {
+fn render_nav() -> VNode {
+    let router = use_router();
     rsx!(nav {
         class = "main-nav",
         ul {
             li {
-                click => move |_| navigate_to(Route::Home),
+                click => router.link(Route::Home),
                 "Home"
             },
             li {
-                click => move |_| navigate_to(Route::Articles),
+                click => router.link(Route::Articles),
                 "Articles"
             },
             li {
-                click => move |_| navigate_to(Route::About),
+                click => router.link(Route::About),
                 "About"
             },
             li {
-                click => move |_| navigate_to(Route::NotFound),
+                click => router.link(Route::NotFound),
                 "Not Found"
             }
         }
     })
 }
 
+// Everything inside #app, as siblings
+fn render_app_content(route: &Route) -> VNode {
+    rsx!(fragment {
+        render_nav(),
+        render_route_content(route),
+        toggle_visibility(),
+        counter_actions(),
+        toggle_theme_button()
+    })
+}
 
 #[wasm_bindgen(start)]
 pub fn start() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
-    main()
+    // Pre-rendered pages already have #app
+    if get_document().get_element_by_id("app").is_some() {
+        hydrate()
+    } else {
+        main()
+    }
 }
 
-pub fn main() -> Result<(), JsValue> {
-    let document = get_document();
-    let body = document.body().expect("not found");
+// The services components look up with `use_context`. Tests provide their
+// own instead
+fn provide_services() {
+    provide_context(ThemeProvider::new());
+    provide_context(Router::default());
+    provide_context(ContentLoader::default());
+}
 
+fn listen_for_popstate() {
     // Set up popstate event listener for back/forward navigation
     let onpopstate = Closure::wrap(Box::new(move |_: web_sys::Event| {
-        render();
+        scheduler::schedule_render();
     }) as Box<dyn FnMut(_)>);
 
     if let Some(window) = web_sys::window() {
         window.set_onpopstate(Some(onpopstate.as_ref().unchecked_ref()));
         onpopstate.forget();
     }
+}
 
-    STATE.with(|state| {
-        let state = state.borrow();
+pub fn main() -> Result<(), JsValue> {
+    let document = get_document();
+    let body = document.body().expect("not found");
 
-        // let content = load_content();
+    listen_for_popstate();
+    provide_services();
 
-        let current_route = get_current_route();
+    // let content = load_content();
 
-        // Pre-compute the class name
-        let class_name = {
-            let mut class = String::with_capacity(32); // Preallocate with reasonable size
-            class.push_str("app-container ");
-            class.push_str(state.theme.to_str());
-            class
-        };
+    let current_route = get_current_route();
+    let theme = use_theme();
 
-        let app = rsx!(div {
-            id = "app",
-            class = class_name,
-            render_nav(),
-            render_route_content(&current_route),
-            toggle_visibility(),
-            counter_actions(),
-            toggle_theme_button()
-        });
+    vdom::render(&body, APP.with(|app_ref| rsx!(div {
+        id = "app",
+        ref = app_ref,
+        class = "app-container",
+        class = theme.theme()
+    })));
+    let app = APP.with(NodeRef::get).expect("#app is mounted");
+    vdom::render(&app, render_app_content(&current_route));
 
-        append_child!(body, &app);
+    // Cosmetic, so a failure is reported rather than stopping the app
+    if let Err(error) = theme.apply_theme_to_element(&app) {
+        web_sys::console::error_2(&"Failed to apply initial theme".into(), &error);
+    }
 
-        if let Some(root) = document.get_element_by_id("app") {
-            state.theme_provider.apply_theme_to_element(&root)
-                .expect("Failed to apply initial theme");
-        }
-    });
+    Ok(())
+}
+
+/// Starts the app on a page whose `#app` was rendered ahead of time, taking
+/// over its markup instead of rebuilding it.
+pub fn hydrate() -> Result<(), JsValue> {
+    let document = get_document();
+    let body = document.body().expect("not found");
+    let app = document.get_element_by_id("app").expect("#app is pre-rendered");
+
+    listen_for_popstate();
+    provide_services();
+
+    let current_route = get_current_route();
+    STATE.with(|state| state.borrow_mut().current_route = current_route.clone());
+
+    let theme = use_theme();
+
+    // The content first: #app is its own root, so hydrating the body
+    // leaves what it claimed alone
+    vdom::hydrate(&app, render_app_content(&current_route));
+    vdom::hydrate(&body, APP.with(|app_ref| rsx!(div {
+        id = "app",
+        ref = app_ref,
+        class = "app-container",
+        class = theme.theme()
+    })));
+
+    // Cosmetic, so a failure is reported rather than stopping the app
+    if let Err(error) = theme.apply_theme_to_element(&app) {
+        web_sys::console::error_2(&"Failed to apply initial theme".into(), &error);
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
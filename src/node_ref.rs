@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::Element;
+
+use crate::vdom::BindRef;
+
+/// A handle to an element built by `rsx!`, bound with `ref = node_ref`.
+///
+/// Clones share the same slot, so a handler can hold one clone while the
+/// element is bound through another. `T` is the element type handed back by
+/// `get()`, e.g. `HtmlInputElement`.
+pub struct NodeRef<T> {
+    node: Rc<RefCell<Option<T>>>,
+}
+
+impl<T: JsCast + Clone> NodeRef<T> {
+    pub fn new() -> Self {
+        Self { node: Rc::new(RefCell::new(None)) }
+    }
+
+    /// The bound element, or `None` before the `rsx!` tree has been built
+    /// and after the element was removed.
+    pub fn get(&self) -> Option<T> {
+        self.node.borrow().clone()
+    }
+
+    /// Called by `rsx!` for `ref = node_ref`. Binding replaces any element
+    /// bound earlier, so a ref follows its element across re-renders.
+    pub fn bind(&self, elem: &Element) {
+        let node = elem.clone().dyn_into::<T>().unwrap_or_else(|elem| {
+            panic!("`ref` on `<{}>` does not match its NodeRef type", elem.tag_name().to_lowercase())
+        });
+        *self.node.borrow_mut() = Some(node);
+    }
+}
+
+impl<T: JsCast + Clone> BindRef for NodeRef<T> {
+    fn bind(&self, elem: &Element) {
+        NodeRef::bind(self, elem);
+    }
+
+    // Another element may have taken the ref over since
+    fn unbind(&self, elem: &Element) {
+        let mut node = self.node.borrow_mut();
+        if node.as_ref().is_some_and(|node| node.unchecked_ref::<Element>().is_same_node(Some(elem))) {
+            *node = None;
+        }
+    }
+}
+
+impl<T: JsCast + Clone> Default for NodeRef<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for NodeRef<T> {
+    fn clone(&self) -> Self {
+        Self { node: self.node.clone() }
+    }
+}
+
+impl<T> fmt::Debug for NodeRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NodeRef")
+            .field("bound", &self.node.borrow().is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rsx, vdom};
+    use wasm_bindgen_test::*;
+    use web_sys::HtmlInputElement;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_ref_binds_typed_element() {
+        let input_ref = NodeRef::<HtmlInputElement>::new();
+        assert!(input_ref.get().is_none());
+
+        let elem = vdom::mount(rsx!(div {
+            input { ref = input_ref, .value = "hello" }
+        }));
+
+        let input = input_ref.get().unwrap();
+        assert_eq!(input.value(), "hello");
+        assert!(elem.contains(Some(&input)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_clones_share_binding_across_renders() {
+        let input_ref = NodeRef::<HtmlInputElement>::new();
+        let handler_ref = input_ref.clone();
+
+        let first = vdom::mount(rsx!(input { ref = input_ref }));
+        let second = vdom::mount(rsx!(input { ref = input_ref }));
+
+        let bound = handler_ref.get().unwrap();
+        assert!(!first.contains(Some(&bound)));
+        assert!(second.contains(Some(&bound)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_ref_is_cleared_when_its_element_goes() {
+        let input_ref = NodeRef::<HtmlInputElement>::new();
+        let container = vdom::mount(rsx!(div { input { ref = input_ref } }));
+        assert!(input_ref.get().is_some());
+
+        // Dropping `ref` from a kept element clears it too
+        vdom::render(&container, rsx!(div { input { } }));
+        assert!(input_ref.get().is_none());
+
+        vdom::render(&container, rsx!(div { input { ref = input_ref } }));
+        vdom::render(&container, rsx!(div { }));
+        assert!(input_ref.get().is_none());
+
+        vdom::render(&container, rsx!(div { p { input { ref = input_ref } } }));
+        vdom::unmount(&container);
+        assert!(input_ref.get().is_none());
+    }
+}
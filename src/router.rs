@@ -1,6 +1,8 @@
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use web_sys::{window, console, History};
 use crate::state::STATE;
+use crate::vdom::use_context;
 use crate::log_stats;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -12,9 +14,14 @@ pub enum Route {
 }
 
 impl Route {
+    /// Every route, in the order the nav lists them.
+    pub const ALL: [Route; 4] = [Route::Home, Route::Articles, Route::About, Route::NotFound];
+
+    /// The route at `path`, with or without a trailing slash: pre-rendered
+    /// pages are served from `/articles/` as `articles/index.html`.
     pub fn from_path(path: &str) -> Self {
-        match path {
-            "/" | "" => Route::Home,
+        match path.trim_end_matches('/') {
+            "" => Route::Home,
             "/articles" => Route::Articles,
             "/about" => Route::About,
             _ => Route::NotFound,
@@ -50,13 +57,47 @@ pub fn navigate_to(route: Route) {
         });
         
         // Manually trigger a render since pushState doesn't fire popstate
-        crate::render();
+        crate::scheduler::schedule_render();
     }
 
     // Log performance stats after navigation
     // log_stats();
 }
 
+/// Sends the app to a route. The default one goes through `navigate_to`;
+/// tests provide one that records the route instead.
+#[derive(Clone)]
+pub struct Router {
+    navigate: Rc<dyn Fn(Route)>,
+}
+
+impl Router {
+    pub fn new(navigate: impl Fn(Route) + 'static) -> Self {
+        Self { navigate: Rc::new(navigate) }
+    }
+
+    pub fn navigate(&self, route: Route) {
+        (self.navigate)(route)
+    }
+
+    // `click => router.link(Route::About)`
+    pub fn link<E>(&self, route: Route) -> impl FnMut(E) {
+        let router = self.clone();
+        move |_| router.navigate(route.clone())
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new(navigate_to)
+    }
+}
+
+/// The provided `Router`, or one that changes the page's history.
+pub fn use_router() -> Router {
+    use_context().unwrap_or_default()
+}
+
 pub fn get_current_route() -> Route {
     window()
         .and_then(|win| {
@@ -126,7 +167,9 @@ mod tests {
             ("", Route::Home),
             ("/", Route::Home),
             ("/articles", Route::Articles),
+            ("/articles/", Route::Articles),
             ("/about", Route::About),
+            ("/about/", Route::About),
             ("/invalid", Route::NotFound),
             ("/random", Route::NotFound),
         ];
@@ -141,4 +184,24 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[wasm_bindgen_test]
+    fn test_nav_goes_through_the_provided_router() {
+        use std::cell::RefCell;
+        use crate::{provide_context, render_nav, vdom, with_root_context};
+
+        let visited = Rc::new(RefCell::new(Vec::new()));
+        let log = visited.clone();
+        with_root_context(|| {
+            provide_context(Router::new(move |route| log.borrow_mut().push(route)));
+
+            let container = vdom::mount(render_nav());
+            let links = container.query_selector_all("li").unwrap();
+            for i in [2, 1] {
+                links.get(i).unwrap().unchecked_into::<web_sys::HtmlElement>().click();
+            }
+        });
+
+        assert_eq!(*visited.borrow(), [Route::About, Route::Articles]);
+    }
+}
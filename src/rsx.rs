@@ -1,185 +1,660 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::Element;
-
-#[macro_export]
-macro_rules! rsx {
-    // Empty element
-    ($tag:ident { }) => {{
-        web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap()
-            .create_element(stringify!($tag))
-            .unwrap()
-    }};
-
-    // Mixed attributes and content in any order
-    ($tag:ident { $($item:tt)+}) => {{
-        let elem = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap()
-            .create_element(stringify!($tag))
-            .unwrap();
-        
-        rsx_internal!(elem, $($item)+);
-        elem
-    }};
-}
-
-#[macro_export]
-macro_rules! rsx_internal {
-    // Base case - nothing left to process
-    ($elem:ident,) => {};
-
-    // Single string literal (no comma)
-    ($elem:ident, $text:literal) => {
-        $elem.set_text_content(Some($text));
-    };
+use web_sys::{CssStyleDeclaration, Element};
+use crate::events;
+use crate::utils::get_document;
+use crate::vdom::{Binding, BindingKind, ElementItems, PropValue, VElement, VNode, VText};
+use crate::{Memo, NodeRef, Signal};
 
-    // Single attribute (no comma)
-    ($elem:ident, $attr:ident = $value:expr) => {
-        $elem.set_attribute(stringify!($attr), &$value.to_string()).unwrap();
-    };
+// Generated code names event types through this path
+#[doc(hidden)]
+pub use web_sys;
 
-    // Single event handler (no comma)
-($elem:ident, $event:ident => $handler:expr) => {
-    let closure = Closure::wrap(Box::new($handler) as Box<dyn FnMut(web_sys::Event)>);
-    $elem.add_event_listener_with_callback(
-        stringify!($event),
-        closure.as_ref().unchecked_ref()
-    ).unwrap();
-    closure.forget();
-};
-
-// Event handler followed by more items
-($elem:ident, $event:ident => $handler:expr, $($rest:tt)*) => {
-    let closure = Closure::wrap(Box::new($handler) as Box<dyn FnMut(web_sys::Event)>);
-    $elem.add_event_listener_with_callback(
-        stringify!($event),
-        closure.as_ref().unchecked_ref()
-    ).unwrap();
-    closure.forget();
-    rsx_internal!($elem, $($rest)*);
-};
-
-    // Handle expression with @ (no comma)
-    ($elem:ident, @$text:expr) => {
-        let text_content = $text;
-        $elem.set_text_content(Some(&text_content.to_string()));
-    };
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
 
-    // Handle expression with @ followed by more items
-    ($elem:ident, @$text:expr, $($rest:tt)*) => {
-        let text_content = $text;
-        $elem.set_text_content(Some(&text_content.to_string()));
-        rsx_internal!($elem, $($rest)*);
-    };
+/// Namespace an `rsx!` element is created in. `rsx!` works out the namespace
+/// of every element at compile time: `svg` and `math` start their own subtree
+/// and `foreignObject` switches back to HTML.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Namespace {
+    Html,
+    Svg,
+    MathMl,
+}
 
-    // Multiple items with comma
-    ($elem:ident, $text:literal, $($rest:tt)*) => {
-        $elem.set_text_content(Some($text));
-        rsx_internal!($elem, $($rest)*);
-    };
+impl Namespace {
+    pub fn uri(self) -> Option<&'static str> {
+        match self {
+            Namespace::Html => None,
+            Namespace::Svg => Some(SVG_NAMESPACE),
+            Namespace::MathMl => Some(MATHML_NAMESPACE),
+        }
+    }
+}
+
+pub fn create_element(namespace: Namespace, tag: &str) -> Element {
+    let document = get_document();
+    match namespace.uri() {
+        None => document.create_element(tag),
+        Some(uri) => document.create_element_ns(Some(uri), tag),
+    }
+    .unwrap()
+}
+
+/// Values accepted on the right-hand side of an `rsx!` attribute.
+///
+/// `bool` follows HTML boolean attribute semantics: `true` adds the attribute
+/// and `false` leaves it off. `None` leaves it off as well.
+pub trait AttributeValue {
+    fn attribute_value(&self) -> Option<String>;
+
+    fn apply_to(&self, items: &mut ElementItems, name: &'static str) {
+        items.attributes.push((name, self.attribute_value()));
+    }
+}
+
+impl AttributeValue for bool {
+    fn attribute_value(&self) -> Option<String> {
+        self.then(String::new)
+    }
+}
+
+impl AttributeValue for str {
+    fn attribute_value(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+impl AttributeValue for String {
+    fn attribute_value(&self) -> Option<String> {
+        Some(self.clone())
+    }
+}
 
-    ($elem:ident, $attr:ident = $value:expr, $($rest:tt)*) => {
-        $elem.set_attribute(stringify!($attr), &$value.to_string()).unwrap();
-        rsx_internal!($elem, $($rest)*);
+impl<T: AttributeValue + ?Sized> AttributeValue for &T {
+    fn attribute_value(&self) -> Option<String> {
+        (**self).attribute_value()
+    }
+}
+
+impl<T: AttributeValue> AttributeValue for Option<T> {
+    fn attribute_value(&self) -> Option<String> {
+        self.as_ref().and_then(AttributeValue::attribute_value)
+    }
+}
+
+macro_rules! impl_attribute_value_display {
+    ($($ty:ty),*) => {
+        $(impl AttributeValue for $ty {
+            fn attribute_value(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+        })*
     };
+}
 
-//     // Single keyboard event handler (no comma)
-// ($elem:ident, $event:ident => keyboard $handler:expr) => {
-//     let closure = Closure::wrap(Box::new(|e: web_sys::Event| {
-//         if let Ok(e) = e.dyn_into::<web_sys::KeyboardEvent>() {
-//             ($handler)(e)
-//         }
-//     }) as Box<dyn FnMut(web_sys::Event)>);
-//     $elem.add_event_listener_with_callback(
-//         stringify!($event),
-//         closure.as_ref().unchecked_ref()
-//     ).unwrap();
-//     closure.forget();
-// };
-
-// // Keyboard event handler followed by more items
-// ($elem:ident, $event:ident => keyboard $handler:expr, $($rest:tt)*) => {
-//     let closure = Closure::wrap(Box::new(|e: web_sys::Event| {
-//         if let Ok(e) = e.dyn_into::<web_sys::KeyboardEvent>() {
-//             ($handler)(e)
-//         }
-//     }) as Box<dyn FnMut(web_sys::Event)>);
-//     $elem.add_event_listener_with_callback(
-//         stringify!($event),
-//         closure.as_ref().unchecked_ref()
-//     ).unwrap();
-//     closure.forget();
-//     rsx_internal!($elem, $($rest)*);
-// };
-
-    // Handle nested element with content
-    ($elem:ident, $child:ident { $($child_content:tt)+ }) => {{
-        let child_elem = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap()
-            .create_element(stringify!($child))
-            .unwrap();
-        rsx_internal!(child_elem, $($child_content)+);
-        $elem.append_child(&child_elem.unchecked_into::<web_sys::Node>()).unwrap();
-    }};
-
-    // Handle nested element with content followed by more items
-    ($elem:ident, $child:ident { $($child_content:tt)+ }, $($rest:tt)*) => {{
-        let child_elem = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap()
-            .create_element(stringify!($child))
-            .unwrap();
-        rsx_internal!(child_elem, $($child_content)+);
-        $elem.append_child(&child_elem.unchecked_into::<web_sys::Node>()).unwrap();
-        rsx_internal!($elem, $($rest)*);
-    }};
-
-    // Handle iterator mapping (with comma)
-    ($elem:ident, $iter:expr, => |$index:ident, $item:ident| $body:expr) => {
-        let elements = $iter.map(|($index, $item)| $body).collect::<Vec<_>>();
-        for element in elements {
-            $elem.append_child(&element.unchecked_into::<web_sys::Node>()).unwrap();
+impl_attribute_value_display!(char, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Values accepted by `class = ...`. Classes are collected into the
+/// element's class list, so they combine with `class:name = condition`
+/// toggles.
+///
+/// A string may hold several space separated classes. Lists, arrays and
+/// `classes(iter)` add one class per item, and `None` adds nothing.
+pub trait ClassValue {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>);
+
+    fn apply_to(&self, items: &mut ElementItems) {
+        self.add_to(&mut items.classes);
+    }
+}
+
+impl ClassValue for str {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        classes.extend(self.split_whitespace().map(|class| Cow::Owned(class.to_string())));
+    }
+}
+
+impl ClassValue for String {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        self.as_str().add_to(classes);
+    }
+}
+
+impl<T: ClassValue + ?Sized> ClassValue for &T {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        (**self).add_to(classes);
+    }
+}
+
+impl<T: ClassValue> ClassValue for Option<T> {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        if let Some(class) = self {
+            class.add_to(classes);
         }
-    };
+    }
+}
 
-    // Handle iterator mapping followed by more items (with comma)
-    ($elem:ident, $iter:expr, => |$index:ident, $item:ident| $body:expr, $($rest:tt)*) => {
-        let elements = $iter.map(|($index, $item)| $body).collect::<Vec<_>>();
-        for element in elements {
-            $elem.append_child(&element.unchecked_into::<web_sys::Node>()).unwrap();
+impl<T: ClassValue> ClassValue for [T] {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        for class in self {
+            class.add_to(classes);
         }
-        rsx_internal!($elem, $($rest)*);
-    };
+    }
+}
 
+impl<T: ClassValue, const N: usize> ClassValue for [T; N] {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        self.as_slice().add_to(classes);
+    }
+}
+
+impl<T: ClassValue> ClassValue for Vec<T> {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        self.as_slice().add_to(classes);
+    }
+}
+
+/// Class list built from an iterator: `class = classes(tags.iter().map(...))`.
+pub struct Classes<I>(std::cell::RefCell<Option<I>>);
+
+pub fn classes<I>(iter: I) -> Classes<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: ClassValue,
+{
+    Classes(std::cell::RefCell::new(Some(iter.into_iter())))
+}
 
-    // Handle child element
-    ($elem:ident, $child:expr) => {
-        $elem.append_child(&$child.unchecked_into::<web_sys::Node>()).unwrap();
+impl<I> ClassValue for Classes<I>
+where
+    I: Iterator,
+    I::Item: ClassValue,
+{
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        for class in self.0.borrow_mut().take().into_iter().flatten() {
+            class.add_to(classes);
+        }
+    }
+}
+
+/// Inline style of any element: HTML, SVG and MathML elements all have one.
+pub fn style(elem: &Element) -> CssStyleDeclaration {
+    js_sys::Reflect::get(elem, &JsValue::from_str("style"))
+        .unwrap()
+        .unchecked_into()
+}
+
+/// Values accepted in a `style = { property: value }` map. `None` leaves
+/// the property unset.
+pub trait StyleValue {
+    fn style_value(&self) -> Option<String>;
+
+    fn apply_to(&self, items: &mut ElementItems, property: &'static str) {
+        items.styles.push((property, self.style_value()));
+    }
+}
+
+impl StyleValue for str {
+    fn style_value(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+impl StyleValue for String {
+    fn style_value(&self) -> Option<String> {
+        Some(self.clone())
+    }
+}
+
+impl<T: StyleValue + ?Sized> StyleValue for &T {
+    fn style_value(&self) -> Option<String> {
+        (**self).style_value()
+    }
+}
+
+impl<T: StyleValue> StyleValue for Option<T> {
+    fn style_value(&self) -> Option<String> {
+        self.as_ref().and_then(StyleValue::style_value)
+    }
+}
+
+macro_rules! impl_style_value_display {
+    ($($ty:ty),*) => {
+        $(impl StyleValue for $ty {
+            fn style_value(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+        })*
     };
+}
+
+// Unitless values only, e.g. `opacity`, `z-index`, `flex-grow`
+impl_style_value_display!(i32, i64, u32, u64, usize, f32, f64);
+
+/// Values accepted by `@expr`: anything `Display`, or a signal to follow.
+pub trait TextValue {
+    fn to_text(&self) -> VText;
+}
+
+impl<T: Display + ?Sized> TextValue for T {
+    fn to_text(&self) -> VText {
+        VText::new(self.to_string())
+    }
+}
 
-    // Handle child element followed by more items
-    ($elem:ident, $child:expr, $($rest:tt)*) => {
-        $elem.append_child(&$child.unchecked_into::<web_sys::Node>()).unwrap();
-        rsx_internal!($elem, $($rest)*);
+pub fn text<V: TextValue + ?Sized>(value: &V) -> VText {
+    value.to_text()
+}
+
+// A signal or memo given to `@`, an attribute, `class` or a style binds it:
+// the DOM follows it without a render
+macro_rules! impl_reactive_values {
+    ($($source:ident),*) => {
+        $(impl<T: Display + 'static> TextValue for $source<T> {
+            fn to_text(&self) -> VText {
+                let source = self.clone();
+                VText::bound(self.id(), move || source.with(T::to_string))
+            }
+        }
+
+        impl<T: AttributeValue + 'static> AttributeValue for $source<T> {
+            fn attribute_value(&self) -> Option<String> {
+                self.with_untracked(T::attribute_value)
+            }
+
+            fn apply_to(&self, items: &mut ElementItems, name: &'static str) {
+                let source = self.clone();
+                let value = Rc::new(move || source.with(T::attribute_value));
+                items.bindings.push(Binding::new(self.id(), BindingKind::Attribute(name, value)));
+            }
+        }
+
+        impl<T: ClassValue + 'static> ClassValue for $source<T> {
+            fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+                self.with_untracked(|value| value.add_to(classes));
+            }
+
+            fn apply_to(&self, items: &mut ElementItems) {
+                let source = self.clone();
+                let classes = Rc::new(move || {
+                    let mut classes = Vec::new();
+                    source.with(|value| value.add_to(&mut classes));
+                    classes
+                });
+                items.bindings.push(Binding::new(self.id(), BindingKind::Class(classes)));
+            }
+        }
+
+        impl<T: StyleValue + 'static> StyleValue for $source<T> {
+            fn style_value(&self) -> Option<String> {
+                self.with_untracked(T::style_value)
+            }
+
+            fn apply_to(&self, items: &mut ElementItems, property: &'static str) {
+                let source = self.clone();
+                let value = Rc::new(move || source.with(T::style_value));
+                items.bindings.push(Binding::new(self.id(), BindingKind::Style(property, value)));
+            }
+        })*
     };
+}
+
+impl_reactive_values!(Signal, Memo);
+
+/// What `rsx!` sets attributes, classes, styles and handlers on: a whole
+/// element, or the items of an element that lives in a template.
+pub trait Items {
+    fn items(&mut self) -> &mut ElementItems;
+}
+
+impl Items for VElement {
+    fn items(&mut self) -> &mut ElementItems {
+        &mut self.items
+    }
+}
+
+impl Items for ElementItems {
+    fn items(&mut self) -> &mut ElementItems {
+        self
+    }
+}
+
+/// What `rsx!` adds children to: elements and fragments.
+pub trait Parent {
+    fn children(&mut self) -> &mut Vec<VNode>;
+}
+
+impl Parent for VElement {
+    fn children(&mut self) -> &mut Vec<VNode> {
+        &mut self.children
+    }
+}
+
+impl Parent for Vec<VNode> {
+    fn children(&mut self) -> &mut Vec<VNode> {
+        self
+    }
+}
+
+pub fn set_attribute<V: AttributeValue + ?Sized>(elem: &mut impl Items, name: &'static str, value: &V) {
+    value.apply_to(elem.items(), name);
+}
+
+/// Sets a DOM property (as opposed to an attribute) on an element, so form
+/// controls reflect live state: `.value`, `.checked`, `.disabled`, ...
+pub fn set_property(elem: &mut impl Items, name: &'static str, value: impl Into<PropValue>) {
+    elem.items().properties.push((name, value.into()));
+}
+
+pub fn add_class<V: ClassValue + ?Sized>(elem: &mut impl Items, value: &V) {
+    value.apply_to(elem.items());
+}
+
+/// `class:name = condition`. A false condition only leaves the toggle's
+/// class out: one `class = ...` adds stays, as it does when a template bakes
+/// it into the element.
+pub fn toggle_class(elem: &mut impl Items, class: &'static str, condition: bool) {
+    if condition {
+        elem.items().classes.push(Cow::Borrowed(class));
+    }
+}
+
+pub fn set_style<V: StyleValue + ?Sized>(elem: &mut impl Items, property: &'static str, value: &V) {
+    value.apply_to(elem.items(), property);
+}
+
+/// Registers `handler` for `event`. `rsx!` picks `E` from the event name,
+/// so handlers receive `MouseEvent`, `KeyboardEvent`, ... directly. The
+/// listener is attached when the element is mounted and removed with it.
+pub fn add_event_listener<E, F>(elem: &mut impl Items, event: &'static str, handler: F)
+where
+    E: JsCast + 'static,
+    F: FnMut(E) + 'static,
+{
+    elem.items().listeners.push((event, events::handler(handler)));
+}
+
+/// `ref = node_ref`: binds the element once it is mounted.
+pub fn bind_ref<T: JsCast + Clone + 'static>(elem: &mut impl Items, node_ref: &NodeRef<T>) {
+    elem.items().node_refs.push(Box::new(node_ref.clone()));
+}
+
+/// `key = ...`: identifies the element among its siblings, so a list keeps
+/// each item's DOM when items are added, removed or reordered.
+pub fn set_key<K: Display + ?Sized>(elem: &mut VElement, key: &K) {
+    elem.key = Some(key.to_string());
+}
+
+pub fn append_text(parent: &mut impl Parent, text: impl Into<Cow<'static, str>>) {
+    parent.children().push(VNode::text(text));
+}
+
+pub fn append_child(parent: &mut impl Parent, child: impl Into<VNode>) {
+    child.into().flatten_into(parent.children());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rsx, vdom};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
 
-    // // Handle if-else condition (no comma)
-    // ($elem:ident, if ($cond:expr) { $then:expr } else { $else:expr }) => {
-    //     let text = if $cond { $then } else { $else };
-    //     $elem.set_text_content(Some(&text.to_string()));
-    // };
-
-    // // Handle if-else condition followed by more items
-    // ($elem:ident, if ($cond:expr) { $then:expr } else { $else:expr }, $($rest:tt)*) => {
-    //     let text = if $cond { $then } else { $else };
-    //     $elem.set_text_content(Some(&text.to_string()));
-    //     rsx_internal!($elem, $($rest)*);
-    // };
-}
\ No newline at end of file
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn mount(node: VNode) -> Element {
+        vdom::mount(node).first_element_child().unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_mixed_text_and_children_keep_source_order() {
+        let elem = mount(rsx!(div {
+            "Counter: ",
+            span { "0" },
+            @" items",
+            class = "mixed"
+        }));
+
+        assert_eq!(elem.inner_html(), "Counter: <span>0</span> items");
+        assert_eq!(elem.child_nodes().length(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_conditional_branches_render_children() {
+        let completed = true;
+        let note: Option<&str> = None;
+        let count = 2;
+
+        let elem = mount(rsx!(div {
+            if completed {
+                span { "done" }
+            } else {
+                span { "pending" }
+            },
+            if let Some(note) = note {
+                p { @note }
+            },
+            match count {
+                0 => { "none" },
+                1 => { "one" },
+                n if n > 1 => { em { "many" } },
+                _ => {}
+            }
+        }));
+
+        assert_eq!(elem.inner_html(), "<span>done</span><em>many</em>");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_event_handlers_receive_typed_events() {
+        let clicked = Rc::new(Cell::new(false));
+        let handler_clicked = clicked.clone();
+
+        let elem = mount(rsx!(button {
+            click => move |e: web_sys::MouseEvent| handler_clicked.set(e.button() == 0),
+            "Click"
+        }));
+        elem.unchecked_ref::<web_sys::HtmlElement>().click();
+
+        assert!(clicked.get());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_boolean_attributes_and_properties() {
+        let elem = mount(rsx!(div {
+            input { type = "checkbox", checked = false, disabled = true },
+            input { type = "checkbox", .checked = true }
+        }));
+
+        let inputs = elem.query_selector_all("input").unwrap();
+        let first = inputs.get(0).unwrap().unchecked_into::<web_sys::HtmlInputElement>();
+        let second = inputs.get(1).unwrap().unchecked_into::<web_sys::HtmlInputElement>();
+
+        assert!(!first.has_attribute("checked"));
+        assert!(!first.checked());
+        assert_eq!(first.get_attribute("disabled").as_deref(), Some(""));
+        assert!(second.checked());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hyphenated_names_and_custom_elements() {
+        let elem = mount(rsx!(my-widget {
+            data-id = 7,
+            aria-label = "Widget",
+            label { for = "name", "Name" },
+            "xml:lang" = "en",
+            todo-item-row { }
+        }));
+
+        assert_eq!(elem.tag_name().to_lowercase(), "my-widget");
+        assert_eq!(elem.get_attribute("data-id").as_deref(), Some("7"));
+        assert_eq!(elem.get_attribute("aria-label").as_deref(), Some("Widget"));
+        assert_eq!(elem.get_attribute("xml:lang").as_deref(), Some("en"));
+        assert_eq!(elem.inner_html(), "<label for=\"name\">Name</label><todo-item-row></todo-item-row>");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_svg_and_math_subtrees_use_their_namespace() {
+        let elem = mount(rsx!(div {
+            svg {
+                viewBox = "0 0 24 24",
+                path { d = "M0 0h24v24H0z" },
+                foreignObject { span { "label" } }
+            },
+            math { mi { "x" } }
+        }));
+
+        let namespace_of = |selector: &str| elem.query_selector(selector).unwrap().unwrap().namespace_uri();
+
+        assert_eq!(elem.namespace_uri().as_deref(), Some("http://www.w3.org/1999/xhtml"));
+        assert_eq!(namespace_of("svg").as_deref(), Some(SVG_NAMESPACE));
+        assert_eq!(namespace_of("path").as_deref(), Some(SVG_NAMESPACE));
+        assert_eq!(namespace_of("span").as_deref(), Some("http://www.w3.org/1999/xhtml"));
+        assert_eq!(namespace_of("mi").as_deref(), Some(MATHML_NAMESPACE));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_class_toggles_and_lists() {
+        let completed = true;
+        let tags = vec!["rust", "wasm"];
+
+        let elem = mount(rsx!(div {
+            class = "todo-item  card",
+            class:completed = completed,
+            class:hidden = !completed,
+            span { class = classes(tags.iter().map(|tag| format!("tag-{}", tag))) },
+            span { class = ["a", "b"], class = None::<&str> }
+        }));
+        let spans = elem.query_selector_all("span").unwrap();
+        let class_of = |i| spans.get(i).unwrap().unchecked_into::<Element>().class_name();
+
+        assert_eq!(elem.class_name(), "todo-item card completed");
+        assert_eq!(class_of(0), "tag-rust tag-wasm");
+        assert_eq!(class_of(1), "a b");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_style_maps_set_declarations() {
+        let color = String::from("red");
+        let width: Option<&str> = None;
+
+        let elem = mount(rsx!(div {
+            style = { display: "none", color: color, background-color: "blue", opacity: 0.5, width: width },
+            svg { style = { "--accent": "#fff" } }
+        }));
+        let declarations = style(&elem);
+
+        assert_eq!(declarations.get_property_value("display").unwrap(), "none");
+        assert_eq!(declarations.get_property_value("color").unwrap(), "red");
+        assert_eq!(declarations.get_property_value("background-color").unwrap(), "blue");
+        assert_eq!(declarations.get_property_value("opacity").unwrap(), "0.5");
+        assert_eq!(declarations.get_property_value("width").unwrap(), "");
+
+        let svg = elem.query_selector("svg").unwrap().unwrap();
+        assert_eq!(style(&svg).get_property_value("--accent").unwrap(), "#fff");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_templates_patch_dynamic_holes() {
+        let clicks = Rc::new(Cell::new(0));
+        let render = |label: &str, items: &[&str]| {
+            let clicks = clicks.clone();
+            rsx!(nav {
+                class = "main-nav",
+                ul {
+                    li { "Home", click => move |_| clicks.set(clicks.get() + 1) },
+                    for item in items { li { @item } },
+                    li { @label }
+                }
+            })
+        };
+
+        let first = mount(render("About", &["a", "b"]));
+        let second = mount(render("Contact", &[]));
+        first.query_selector("li").unwrap().unwrap().unchecked_into::<web_sys::HtmlElement>().click();
+
+        assert_eq!(
+            first.inner_html(),
+            "<ul><li>Home</li><li>a</li><li>b</li><li>About</li></ul>"
+        );
+        assert_eq!(second.inner_html(), "<ul><li>Home</li><li>Contact</li></ul>");
+        assert_eq!(second.class_name(), "main-nav");
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_template_cloning_against_element_by_element() {
+        use crate::performance::{log_stats, measure};
+
+        let todos: Vec<String> = (0..500).map(|i| format!("Todo {}", i)).collect();
+
+        // One `rsx!` per element stays under the template threshold, so this
+        // is what `rsx!` emits without templates: every element built and
+        // diffed by itself
+        let direct = || {
+            mount(rsx!(div {
+                for (index, text) in todos.iter().enumerate() {
+                    rsx!(div {
+                        class = "todo-item",
+                        data-index = index,
+                        rsx!(input { class = "todo-checkbox", type = "checkbox" }),
+                        rsx!(span { class = "todo-text", @text }),
+                        rsx!(button { class = "todo-delete", "Delete" })
+                    })
+                }
+            }))
+        };
+        let templated = || {
+            mount(rsx!(div {
+                for (index, text) in todos.iter().enumerate() {
+                    div {
+                        class = "todo-item",
+                        data-index = index,
+                        input { class = "todo-checkbox", type = "checkbox" },
+                        span { class = "todo-text", @text },
+                        button { class = "todo-delete", "Delete" }
+                    }
+                }
+            }))
+        };
+        assert_eq!(templated().inner_html(), direct().inner_html());
+
+        // Alternating rounds, so GC pauses and warm-up hit both. Timings
+        // depend on the machine, so they are reported, not asserted
+        for _ in 0..5 {
+            measure("todo_list_element_by_element", direct);
+            measure("todo_list_template", templated);
+        }
+        log_stats();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_class_toggles_behave_the_same_in_templates() {
+        // One element is built directly, two make a template
+        let direct = mount(rsx!(span { class = "done", class:done = false, class:open = true }));
+        let templated = mount(rsx!(div { class = "done", class:done = false, class:open = true, span { } }));
+
+        assert_eq!(direct.class_name(), "done open");
+        assert_eq!(templated.class_name(), "done open");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fragments_return_siblings() {
+        let items = ["a", "b"];
+        let fragment = rsx!(fragment {
+            h1 { "Title" },
+            for item in items {
+                fragment { span { @item }, "," }
+            }
+        });
+
+        let container = mount(rsx!(div { fragment }));
+        assert_eq!(container.inner_html(), "<h1>Title</h1><span>a</span>,<span>b</span>,");
+    }
+}
@@ -0,0 +1,123 @@
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+// Animation frame requested for the next render
+struct Frame {
+    id: i32,
+    _callback: Closure<dyn FnMut()>,
+}
+
+thread_local! {
+    static FRAME: RefCell<Option<Frame>> = const { RefCell::new(None) };
+    static APP_DIRTY: Cell<bool> = const { Cell::new(false) };
+    static APP_RENDERS: Cell<usize> = const { Cell::new(0) };
+    // Work for the next frame besides the app render, e.g. components
+    // re-rendering after their own state changed
+    static TASKS: RefCell<Vec<Box<dyn FnOnce()>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks the app dirty. It renders once, on the next animation frame, no
+/// matter how many updates come in before then.
+pub fn schedule_render() {
+    APP_DIRTY.with(|dirty| dirty.set(true));
+    request_frame();
+}
+
+/// Runs `task` on the next animation frame, after the app render if one is
+/// due.
+pub fn schedule(task: impl FnOnce() + 'static) {
+    TASKS.with(|tasks| tasks.borrow_mut().push(Box::new(task)));
+    request_frame();
+}
+
+fn request_frame() {
+    FRAME.with(|frame| {
+        let mut frame = frame.borrow_mut();
+        if frame.is_some() {
+            return;
+        }
+
+        let callback = Closure::wrap(Box::new(|| {
+            // The closure is dropped here while it runs, which wasm-bindgen
+            // defers until it returns
+            FRAME.with(|frame| frame.borrow_mut().take());
+            run();
+        }) as Box<dyn FnMut()>);
+        let id = web_sys::window()
+            .expect("no global `window` exists")
+            .request_animation_frame(callback.as_ref().unchecked_ref())
+            .unwrap();
+        *frame = Some(Frame { id, _callback: callback });
+    });
+}
+
+fn run() {
+    if APP_DIRTY.with(|dirty| dirty.replace(false)) {
+        APP_RENDERS.with(|renders| renders.set(renders.get() + 1));
+        crate::render();
+    }
+    for task in TASKS.with(|tasks| tasks.take()) {
+        task();
+    }
+}
+
+/// Renders now if a render is scheduled, instead of waiting for the frame.
+/// Meant for tests and for code that has to measure the DOM right after an
+/// update.
+pub fn flush_sync() {
+    let Some(frame) = FRAME.with(|frame| frame.borrow_mut().take()) else {
+        return;
+    };
+    if let Some(window) = web_sys::window() {
+        let _ = window.cancel_animation_frame(frame.id);
+    }
+    run();
+}
+
+/// How many times the app has rendered, for checking that updates batch.
+pub fn render_count() -> usize {
+    APP_RENDERS.with(Cell::get)
+}
+
+/// Whether a render is waiting for the next animation frame.
+pub fn is_scheduled() -> bool {
+    FRAME.with(|frame| frame.borrow().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{dispatch, STATE};
+    use crate::state::actions::{Action, TodoOperation};
+    use crate::components::todo::add_todo;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_updates_are_batched_into_one_render() {
+        STATE.with(|state| state.borrow_mut().todos.clear());
+        flush_sync();
+        let renders = render_count();
+
+        add_todo("one".to_string());
+        add_todo("two".to_string());
+        dispatch(Action::Todo(TodoOperation::Toggle(0)));
+        let frame_id = || FRAME.with(|frame| frame.borrow().as_ref().map(|frame| frame.id));
+        let frame = frame_id();
+
+        assert!(is_scheduled());
+        add_todo("three".to_string());
+        assert_eq!(frame_id(), frame);
+        assert_eq!(render_count(), renders);
+
+        flush_sync();
+        assert!(!is_scheduled());
+        assert_eq!(render_count(), renders + 1);
+
+        // Nothing left to render
+        flush_sync();
+        assert_eq!(render_count(), renders + 1);
+    }
+}
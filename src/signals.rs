@@ -0,0 +1,352 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+thread_local! {
+    // Effect currently running, which signals read meanwhile subscribe
+    static OBSERVER: RefCell<Option<Weak<EffectState>>> = const { RefCell::new(None) };
+}
+
+// Effects to re-run when a signal changes
+#[derive(Default)]
+struct Subscribers {
+    effects: RefCell<Vec<Weak<EffectState>>>,
+}
+
+impl Subscribers {
+    fn track(self: &Rc<Self>) {
+        let Some(effect) = OBSERVER.with(|observer| observer.borrow().as_ref().and_then(Weak::upgrade)) else {
+            return;
+        };
+        let mut effects = self.effects.borrow_mut();
+        if !effects.iter().any(|subscribed| std::ptr::eq(subscribed.as_ptr(), Rc::as_ptr(&effect))) {
+            effects.push(Rc::downgrade(&effect));
+            effect.sources.borrow_mut().push(Rc::downgrade(self));
+        }
+    }
+
+    fn notify(&self) {
+        // Collected first: effects re-subscribe while they run
+        let effects: Vec<_> = self.effects.borrow().iter().filter_map(Weak::upgrade).collect();
+        for effect in effects {
+            effect.run();
+        }
+    }
+
+    fn unsubscribe(&self, effect: *const EffectState) {
+        self.effects.borrow_mut().retain(|subscribed| !std::ptr::eq(subscribed.as_ptr(), effect));
+    }
+}
+
+struct EffectState {
+    run: RefCell<Box<dyn FnMut()>>,
+    sources: RefCell<Vec<Weak<Subscribers>>>,
+}
+
+impl EffectState {
+    fn run(self: &Rc<Self>) {
+        // An effect that sets a signal it reads doesn't recurse into itself,
+        // and stays subscribed to what the run in progress reads
+        let Ok(mut run) = self.run.try_borrow_mut() else {
+            return;
+        };
+
+        // Only the signals read by this run stay subscribed
+        self.unsubscribe();
+        let previous = OBSERVER.with(|observer| observer.replace(Some(Rc::downgrade(self))));
+        run();
+        OBSERVER.with(|observer| *observer.borrow_mut() = previous);
+    }
+
+    fn unsubscribe(&self) {
+        for source in self.sources.take() {
+            if let Some(source) = source.upgrade() {
+                source.unsubscribe(self);
+            }
+        }
+    }
+}
+
+/// A value that notifies the effects reading it when it changes.
+///
+/// Clones share the value. Read it with `get`/`with` inside an `Effect`,
+/// `Memo` or an `rsx!` binding (`@count`, `class = theme`, ...) to subscribe.
+pub struct Signal<T> {
+    value: Rc<RefCell<T>>,
+    subscribers: Rc<Subscribers>,
+}
+
+impl<T> Signal<T> {
+    pub fn new(value: T) -> Self {
+        Self { value: Rc::new(RefCell::new(value)), subscribers: Rc::default() }
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.subscribers.track();
+        self.with_untracked(f)
+    }
+
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.value.borrow())
+    }
+
+    pub fn set(&self, value: T) {
+        *self.value.borrow_mut() = value;
+        self.subscribers.notify();
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.value.borrow_mut());
+        self.subscribers.notify();
+    }
+
+    /// Identifies the signal: clones share it.
+    pub fn id(&self) -> usize {
+        Rc::as_ptr(&self.subscribers) as usize
+    }
+}
+
+impl<T: Clone> Signal<T> {
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+
+    pub fn get_untracked(&self) -> T {
+        self.with_untracked(T::clone)
+    }
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        Self { value: self.value.clone(), subscribers: self.subscribers.clone() }
+    }
+}
+
+impl<T: Default> Default for Signal<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Signal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Signal").field(&*self.value.borrow()).finish()
+    }
+}
+
+/// A value derived from signals. It is recomputed when they change and
+/// only notifies its own readers when the result differs.
+pub struct Memo<T> {
+    signal: Signal<T>,
+    _effect: Rc<Effect>,
+}
+
+impl<T: PartialEq + 'static> Memo<T> {
+    pub fn new(compute: impl Fn() -> T + 'static) -> Self {
+        let slot: Rc<RefCell<Option<Signal<T>>>> = Rc::default();
+        let effect_slot = slot.clone();
+        let effect = Effect::new(move || {
+            let value = compute();
+            let signal = effect_slot.borrow().clone();
+            match signal {
+                Some(signal) => {
+                    if signal.with_untracked(|current| *current != value) {
+                        signal.set(value);
+                    }
+                }
+                None => *effect_slot.borrow_mut() = Some(Signal::new(value)),
+            }
+        });
+        let signal = slot.borrow().clone().unwrap();
+
+        Self { signal, _effect: Rc::new(effect) }
+    }
+}
+
+impl<T> Memo<T> {
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.signal.with(f)
+    }
+
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.signal.with_untracked(f)
+    }
+
+    pub fn id(&self) -> usize {
+        self.signal.id()
+    }
+}
+
+impl<T: Clone> Memo<T> {
+    pub fn get(&self) -> T {
+        self.signal.get()
+    }
+
+    pub fn get_untracked(&self) -> T {
+        self.signal.get_untracked()
+    }
+}
+
+impl<T> Clone for Memo<T> {
+    fn clone(&self) -> Self {
+        Self { signal: self.signal.clone(), _effect: self._effect.clone() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Memo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Memo").field(&*self.signal.value.borrow()).finish()
+    }
+}
+
+/// Runs a closure now and again whenever a signal it read changes.
+/// Dropping the effect stops it.
+pub struct Effect {
+    state: Rc<EffectState>,
+}
+
+impl Effect {
+    pub fn new(run: impl FnMut() + 'static) -> Self {
+        let state = Rc::new(EffectState {
+            run: RefCell::new(Box::new(run)),
+            sources: RefCell::default(),
+        });
+        state.run();
+        Self { state }
+    }
+}
+
+impl Drop for Effect {
+    fn drop(&mut self) {
+        self.state.unsubscribe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_effects_rerun_when_read_signals_change() {
+        let count = Signal::new(1);
+        let runs = Rc::new(Cell::new(0));
+        let seen = Rc::new(Cell::new(0));
+
+        let effect = {
+            let (count, runs, seen) = (count.clone(), runs.clone(), seen.clone());
+            Effect::new(move || {
+                runs.set(runs.get() + 1);
+                seen.set(count.get());
+            })
+        };
+        count.set(2);
+        count.update(|count| *count += 1);
+
+        assert_eq!((runs.get(), seen.get()), (3, 3));
+
+        drop(effect);
+        count.set(4);
+        assert_eq!(runs.get(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_effects_only_follow_signals_of_their_last_run() {
+        let show = Signal::new(true);
+        let label = Signal::new("a");
+        let runs = Rc::new(Cell::new(0));
+
+        let _effect = {
+            let (show, label, runs) = (show.clone(), label.clone(), runs.clone());
+            Effect::new(move || {
+                runs.set(runs.get() + 1);
+                if show.get() {
+                    label.get();
+                }
+            })
+        };
+        show.set(false);
+        label.set("b");
+
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_effects_setting_their_own_signal_keep_following_it() {
+        let count = Signal::new(0);
+        let runs = Rc::new(Cell::new(0));
+
+        // Clamps whatever is set to at most 10
+        let _effect = {
+            let (count, runs) = (count.clone(), runs.clone());
+            Effect::new(move || {
+                runs.set(runs.get() + 1);
+                if count.get() > 10 {
+                    count.set(10);
+                }
+            })
+        };
+        count.set(20);
+        assert_eq!((count.get_untracked(), runs.get()), (10, 2));
+
+        count.set(30);
+        assert_eq!((count.get_untracked(), runs.get()), (10, 3));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memos_skip_unchanged_results() {
+        let count = Signal::new(1);
+        let parity = {
+            let count = count.clone();
+            Memo::new(move || count.get() % 2)
+        };
+        let runs = Rc::new(Cell::new(0));
+
+        let _effect = {
+            let (parity, runs) = (parity.clone(), runs.clone());
+            Effect::new(move || {
+                parity.get();
+                runs.set(runs.get() + 1);
+            })
+        };
+        count.set(3);
+        assert_eq!(runs.get(), 1);
+
+        count.set(4);
+        assert_eq!((runs.get(), parity.get_untracked()), (2, 0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_rsx_bindings_follow_signals() {
+        use crate::{rsx, vdom};
+
+        let count = Signal::new(0);
+        let hidden = Signal::new(false);
+        let label = {
+            let count = count.clone();
+            Memo::new(move || if count.get() == 1 { "one" } else { "many" })
+        };
+        let container = vdom::mount(rsx!(div {
+            span { id = "count", @count },
+            p { hidden = hidden, class = label, style = { "--count": count }, "static" }
+        }));
+        let span = container.query_selector("#count").unwrap().unwrap();
+        let p = container.query_selector("p").unwrap().unwrap();
+
+        count.set(1);
+        hidden.set(true);
+
+        assert_eq!(span.text_content().unwrap(), "1");
+        assert_eq!(p.class_name(), "one");
+        assert!(p.has_attribute("hidden"));
+        assert_eq!(crate::rsx::style(&p).get_property_value("--count").unwrap(), "1");
+
+        // Unmounting drops the bindings with their effects
+        vdom::unmount(&container);
+        count.set(2);
+        assert_eq!(span.text_content().unwrap(), "1");
+    }
+}
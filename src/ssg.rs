@@ -0,0 +1,98 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::content_loader::{parse_content, ContentLoader};
+use crate::router::Route;
+use crate::theme::use_theme;
+use crate::{render_app_content, rsx, vdom};
+
+/// Pre-renders every route into `out_dir`, each page being `template` with
+/// the route's `#app` as its body. Articles come from the `content` file.
+/// Returns the pages written.
+pub fn generate(template: &Path, content: &Path, out_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let template = fs::read_to_string(template)?;
+    let content = parse_content(&fs::read_to_string(content)?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "content.json has no `articles` array"))?;
+
+    // Only these pages read the fixed content
+    vdom::with_root_context(|| {
+        vdom::provide_context(ContentLoader::fixed(content));
+
+        let mut written = Vec::new();
+        for route in &Route::ALL {
+            let html = page(&template, &render_app(route))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the template has no <body>"))?;
+            let path = page_path(out_dir, route);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(&path, html)?;
+            written.push(path);
+        }
+        Ok(written)
+    })
+}
+
+/// `#app` with the content for `route`, as `main()` renders it.
+pub fn render_app(route: &Route) -> String {
+    vdom::render_to_string(rsx!(div {
+        id = "app",
+        class = "app-container",
+        class = use_theme().theme(),
+        render_app_content(route)
+    }))
+}
+
+/// `template` with `app` as the only thing in its body.
+pub fn page(template: &str, app: &str) -> Option<String> {
+    let body = template.find("<body")?;
+    let start = body + template[body..].find('>')? + 1;
+    let end = template.rfind("</body>")?;
+    Some(format!("{}\n{}\n{}", &template[..start], app, &template[end..]))
+}
+
+/// Where the page for `route` goes, e.g. `dist/articles/index.html`.
+pub fn page_path(out_dir: &Path, route: &Route) -> PathBuf {
+    out_dir.join(route.to_path().trim_start_matches('/')).join("index.html")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_go_to_their_route_path() {
+        let dist = Path::new("dist");
+
+        assert_eq!(page_path(dist, &Route::Home), Path::new("dist/index.html"));
+        assert_eq!(page_path(dist, &Route::Articles), Path::new("dist/articles/index.html"));
+        assert_eq!(page_path(dist, &Route::NotFound), Path::new("dist/404/index.html"));
+    }
+
+    #[test]
+    fn test_app_replaces_the_template_body() {
+        let template = "<html><head><title>Bolt</title></head><body class=\"x\">\n</body></html>";
+
+        assert_eq!(
+            page(template, "<div id=\"app\"></div>").unwrap(),
+            "<html><head><title>Bolt</title></head><body class=\"x\">\n<div id=\"app\"></div>\n</body></html>"
+        );
+        assert_eq!(page("<p>no body</p>", ""), None);
+    }
+
+    #[test]
+    fn test_generates_a_page_per_route() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out_dir = std::env::temp_dir().join(format!("bolt-ssg-{}", std::process::id()));
+
+        let written = generate(&root.join("index.html"), &root.join("content.json"), &out_dir).unwrap();
+        let articles = fs::read_to_string(page_path(&out_dir, &Route::Articles)).unwrap();
+        let about = fs::read_to_string(page_path(&out_dir, &Route::About)).unwrap();
+        fs::remove_dir_all(&out_dir).unwrap();
+
+        assert_eq!(written.len(), Route::ALL.len());
+        assert!(articles.contains("<title>Bolt UI</title>"));
+        assert!(articles.contains("<div id=\"app\" class=\"app-container theme-light\"><nav class=\"main-nav\">"));
+        assert!(articles.contains("<h2>Getting Started with Rust</h2>"));
+        assert!(about.contains("<h1>About Us</h1>"));
+    }
+}
@@ -1,8 +1,11 @@
-use crate::theme::{Theme, ThemeProvider};
 use crate::router::Route;  // Update this import
+use crate::rsx::StyleValue;
+use crate::Signal;
 
 #[derive(Clone, Default)]
 pub struct Todo {
+    // Stable across removals, so the list can key items by it
+    pub id: usize,
     pub text: String,
     pub completed: bool,
 }
@@ -14,24 +17,40 @@ pub enum Visibility {
     Hidden,
 }
 
+impl Visibility {
+    // CSS `display` value for the content it applies to
+    pub fn display(self) -> &'static str {
+        match self {
+            Visibility::Shown => "block",
+            Visibility::Hidden => "none",
+        }
+    }
+}
+
+// `style = { display: visibility }`
+impl StyleValue for Visibility {
+    fn style_value(&self) -> Option<String> {
+        Some(self.display().to_string())
+    }
+}
+
 #[derive(Clone)]
 pub struct AppState {
-    pub counter: i32,
-    pub theme: Theme,
-    pub visibility: Visibility,
+    // Signals: what binds them updates without a render
+    pub counter: Signal<i32>,
+    pub visibility: Signal<Visibility>,
     pub todos: Vec<Todo>,
-    pub theme_provider: ThemeProvider,
+    pub next_todo_id: usize,
     pub current_route: Route,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            counter: 0,
-            theme: Theme::default(),
-            visibility: Visibility::default(),
+            counter: Signal::new(0),
+            visibility: Signal::default(),
             todos: Vec::new(),
-            theme_provider: ThemeProvider::new(),
+            next_todo_id: 0,
             current_route: Route::Home,
         }
     }
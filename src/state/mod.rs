@@ -2,7 +2,6 @@ pub mod actions;
 pub mod app_state;
 
 use core::cell::RefCell;
-use crate::utils::get_document;
 use app_state::AppState;
 use actions::Action;
 
@@ -18,52 +17,47 @@ where
         let new_state = updater(state.borrow().clone());
         *state.borrow_mut() = new_state;
     });
-    crate::render();
+    crate::scheduler::schedule_render();
 }
 
 pub fn dispatch(action: Action) {
-    update_state(|mut state| {
-        match action {
-            Action::Counter(op) => {
-                state.counter = match op {
-                    actions::Operation::Increment => state.counter + 1,
-                    actions::Operation::Decrement => state.counter - 1,
+    match action {
+        // Signal-backed state: the bindings reading it update on their own,
+        // so these skip the render `update_state` does
+        Action::Counter(op) => {
+            let counter = STATE.with(|state| state.borrow().counter.clone());
+            counter.update(|counter| {
+                *counter = match op {
+                    actions::Operation::Increment => *counter + 1,
+                    actions::Operation::Decrement => *counter - 1,
                     actions::Operation::Reset => 0,
                 };
-            },
-            Action::Todo(op) => {
-                match op {
-                    actions::TodoOperation::Toggle(index) => {
-                        if let Some(todo) = state.todos.get_mut(index) {
-                            todo.completed = !todo.completed;
-                        }
-                    },
-                    actions::TodoOperation::Remove(index) => {
-                        if index < state.todos.len() {
-                            state.todos.remove(index);
-                        }
-                    },
-                }
-            },
-            Action::ToggleTheme => {
-                state.theme = match state.theme {
-                    crate::theme::Theme::Light => crate::theme::Theme::Dark,
-                    crate::theme::Theme::Dark => crate::theme::Theme::Light,
-                };
-                state.theme_provider.toggle_theme();
-                
-                if let Some(root) = get_document().get_element_by_id("app") {
-                    state.theme_provider.apply_theme_to_element(&root)
-                        .expect("Failed to apply theme");
-                }
-            },
-            Action::ToggleVisibility => {
-                state.visibility = match state.visibility {
+            });
+        },
+        Action::ToggleTheme => crate::theme::use_theme().toggle_theme(),
+        Action::ToggleVisibility => {
+            let visibility = STATE.with(|state| state.borrow().visibility.clone());
+            visibility.update(|visibility| {
+                *visibility = match visibility {
                     app_state::Visibility::Shown => app_state::Visibility::Hidden,
                     app_state::Visibility::Hidden => app_state::Visibility::Shown,
                 };
-            },
-        }
-        state
-    });
-}
\ No newline at end of file
+            });
+        },
+        Action::Todo(op) => update_state(|mut state| {
+            match op {
+                actions::TodoOperation::Toggle(index) => {
+                    if let Some(todo) = state.todos.get_mut(index) {
+                        todo.completed = !todo.completed;
+                    }
+                },
+                actions::TodoOperation::Remove(index) => {
+                    if index < state.todos.len() {
+                        state.todos.remove(index);
+                    }
+                },
+            }
+            state
+        }),
+    }
+}
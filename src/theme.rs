@@ -1,5 +1,9 @@
+use std::borrow::Cow;
 use wasm_bindgen::prelude::*;
 use web_sys::{Element, HtmlElement};
+use crate::rsx::ClassValue;
+use crate::signals::Signal;
+use crate::vdom::use_context;
 
 #[derive(Copy, Clone, Default)]
 pub enum Theme {
@@ -23,23 +27,39 @@ impl std::fmt::Display for Theme {
     }
 }
 
+// `class = theme`
+impl ClassValue for Theme {
+    fn add_to(&self, classes: &mut Vec<Cow<'static, str>>) {
+        classes.push(Cow::Borrowed(self.to_str()));
+    }
+}
+
+/// The app's theme. Clones share it; `main()` provides one as a context,
+/// which `use_theme` returns.
 #[derive(Clone)]
 pub struct ThemeProvider {
-    current_theme: Theme,
+    current_theme: Signal<Theme>,
 }
 
 impl ThemeProvider {
     pub fn new() -> Self {
         Self {
-            current_theme: Theme::Light,
+            current_theme: Signal::default(),
         }
     }
 
-    pub fn toggle_theme(&mut self) {
-        self.current_theme = match self.current_theme {
-            Theme::Light => Theme::Dark,
-            Theme::Dark => Theme::Light,
-        };
+    // `class = provider.theme()` follows the toggles without a render
+    pub fn theme(&self) -> Signal<Theme> {
+        self.current_theme.clone()
+    }
+
+    pub fn toggle_theme(&self) {
+        self.current_theme.update(|theme| {
+            *theme = match theme {
+                Theme::Light => Theme::Dark,
+                Theme::Dark => Theme::Light,
+            };
+        });
     }
 
     pub fn apply_theme_to_element(&self, element: &Element) -> Result<(), JsValue> {
@@ -47,7 +67,7 @@ impl ThemeProvider {
             .ok_or_else(|| JsValue::from_str("Element is not an HtmlElement"))?;
         
         // Add theme class
-        html_element.set_class_name(&format!("app-container {}", self.current_theme.to_str()));
+        html_element.set_class_name(&format!("app-container {}", self.current_theme.get_untracked().to_str()));
         
         Ok(())
     }
@@ -57,4 +77,9 @@ impl Default for ThemeProvider {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// The provided `ThemeProvider`, or a light one nothing else shares.
+pub fn use_theme() -> ThemeProvider {
+    use_context().unwrap_or_default()
+}
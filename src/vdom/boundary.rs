@@ -0,0 +1,297 @@
+use std::cell::RefCell;
+use std::fmt;
+use wasm_bindgen::{JsCast, JsValue};
+
+use super::component::{component, Component};
+use super::context::{provide_context, use_context};
+use super::hooks::{use_state, State};
+use super::patch::is_patching;
+use super::VNode;
+
+thread_local! {
+    // Failures while mounting or patching, with the boundary each belongs to
+    static CAUGHT: RefCell<Vec<(Boundary, RenderError)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Why a subtree couldn't render. `?` converts the `JsValue` errors of
+/// `web_sys` calls, strings and `String`s into one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderError {
+    message: String,
+}
+
+impl RenderError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<JsValue> for RenderError {
+    fn from(value: JsValue) -> Self {
+        let message = match value.dyn_ref::<js_sys::Error>() {
+            Some(error) => String::from(error.message()),
+            None => value.as_string().unwrap_or_else(|| format!("{:?}", value)),
+        };
+        Self::new(message)
+    }
+}
+
+impl From<String> for RenderError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for RenderError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+/// Renders the boundary's children again, e.g. from a "Retry" button in
+/// the fallback.
+#[derive(Clone)]
+pub struct Retry {
+    caught: State<Option<RenderError>>,
+}
+
+impl Retry {
+    pub fn retry(&self) {
+        self.caught.set(None);
+    }
+}
+
+// What a boundary provides to the subtree it renders
+#[derive(Clone)]
+struct Boundary {
+    caught: State<Option<RenderError>>,
+}
+
+/// Hands the error of a DOM call made while mounting or patching to the
+/// nearest boundary, which shows its fallback once the patch is done.
+/// Outside any boundary it panics, like the `unwrap` it stands for.
+pub(super) fn catch<T>(result: Result<T, JsValue>) -> Option<T> {
+    result.map_err(|error| report(use_context(), error)).ok()
+}
+
+/// The boundary around what renders now, for DOM calls made later on its
+/// behalf, like those of the effects that keep bindings up to date.
+pub(super) struct Catch(Option<Boundary>);
+
+impl Catch {
+    pub(super) fn current() -> Self {
+        Self(use_context())
+    }
+
+    /// Like `catch`, for this boundary.
+    pub(super) fn check<T>(&self, result: Result<T, JsValue>) -> Option<T> {
+        result.map_err(|error| report(self.0.clone(), error)).ok()
+    }
+}
+
+fn report(boundary: Option<Boundary>, error: JsValue) {
+    let error = RenderError::from(error);
+    match boundary {
+        Some(boundary) => CAUGHT.with(|caught| caught.borrow_mut().push((boundary, error))),
+        None => panic!("render failed: {}", error),
+    }
+    // An effect rerun by a signal fails outside any patch, so nothing else
+    // would show the fallback. Not from inside the effect, which the new
+    // render would subscribe to what it reads
+    if !is_patching() {
+        crate::scheduler::schedule(show_fallbacks);
+    }
+}
+
+/// Re-renders the boundaries that caught an error in the patch that just
+/// ended, so they show their fallbacks in the same patch.
+pub(super) fn show_fallbacks() {
+    for (boundary, error) in CAUGHT.with(|caught| caught.take()) {
+        if boundary.caught.with(Option::is_none) {
+            boundary.caught.set_now(Some(error));
+        }
+    }
+}
+
+/// Renders what `children` returns, or what `fallback` makes of its error
+/// when it fails. Failed DOM calls while mounting, patching or removing
+/// what it returned count as well, down to a nested boundary, and so do
+/// those of its bindings when a signal changes. Only this subtree is
+/// replaced, so the rest of the page keeps working; `Retry` calls
+/// `children` again.
+///
+/// ```ignore
+/// error_boundary(
+///     || Ok(render_articles(load_content()?)),
+///     |error, retry| rsx!(p { @error, button { click => move |_| retry.retry(), "Retry" } }),
+/// )
+/// ```
+pub fn error_boundary(
+    children: impl Fn() -> Result<VNode, RenderError> + 'static,
+    fallback: impl Fn(&RenderError, Retry) -> VNode + 'static,
+) -> VNode {
+    component::<ErrorBoundary>(BoundaryProps { children: Box::new(children), fallback: Box::new(fallback) })
+}
+
+type Fallback = Box<dyn Fn(&RenderError, Retry) -> VNode>;
+
+struct BoundaryProps {
+    children: Box<dyn Fn() -> Result<VNode, RenderError>>,
+    fallback: Fallback,
+}
+
+struct ErrorBoundary;
+
+impl Component for ErrorBoundary {
+    type Props = BoundaryProps;
+
+    fn create(_props: &BoundaryProps) -> Self {
+        ErrorBoundary
+    }
+
+    fn render(&self, props: &BoundaryProps) -> VNode {
+        let caught = use_state(|| None);
+        let result = match caught.get() {
+            Some(error) => Err(error),
+            None => (props.children)(),
+        };
+        match result {
+            Ok(node) => {
+                // Not to the fallback: its failures go to the boundary around
+                provide_context(Boundary { caught });
+                node
+            }
+            Err(error) => {
+                let message = format!("render failed: {}", error);
+                #[cfg(target_arch = "wasm32")]
+                web_sys::console::error_1(&message.into());
+                #[cfg(not(target_arch = "wasm32"))]
+                eprintln!("{}", message);
+                (props.fallback)(&error, Retry { caught })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::flush_sync;
+    use crate::signals::Signal;
+    use crate::{rsx, vdom};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_failed_children_show_the_fallback_until_retried() {
+        let failing = Rc::new(Cell::new(true));
+        let retry: Rc<RefCell<Option<Retry>>> = Rc::default();
+        let view = || {
+            let (failing, retry) = (failing.clone(), retry.clone());
+            rsx!(div {
+                p { "outside" },
+                error_boundary(
+                    move || match failing.get() {
+                        true => Err("offline".into()),
+                        false => Ok(rsx!(span { "loaded" })),
+                    },
+                    move |error, handle| {
+                        *retry.borrow_mut() = Some(handle);
+                        rsx!(em { "failed: ", @error })
+                    },
+                )
+            })
+        };
+
+        let container = vdom::mount(view());
+        assert_eq!(container.text_content().unwrap(), "outsidefailed: offline");
+
+        failing.set(false);
+        retry.borrow().as_ref().unwrap().retry();
+        flush_sync();
+        assert_eq!(container.text_content().unwrap(), "outsideloaded");
+    }
+
+    // Not a valid attribute name, so `setAttribute` throws
+    fn broken(label: &'static str) -> VNode {
+        rsx!(p { "not valid" = label, @label })
+    }
+
+    #[wasm_bindgen_test]
+    fn test_failures_while_mounting_the_subtree_show_the_fallback() {
+        let container = vdom::mount(rsx!(div {
+            p { "outside" },
+            error_boundary(
+                || Ok(rsx!(section { broken("inside") })),
+                |_, _| rsx!(em { "failed" }),
+            )
+        }));
+
+        assert_eq!(container.text_content().unwrap(), "outsidefailed");
+        assert!(container.query_selector("section").unwrap().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_failures_of_bindings_show_the_fallback() {
+        let invalid = Signal::new(false);
+        let bound = invalid.clone();
+        let container = vdom::mount(rsx!(div {
+            error_boundary(
+                move || Ok(rsx!(p { "not valid" = bound.clone(), "child" })),
+                |_, _| rsx!(em { "failed" }),
+            )
+        }));
+        assert_eq!(container.text_content().unwrap(), "child");
+
+        // `true` sets the attribute, which throws
+        invalid.set(true);
+        flush_sync();
+        assert_eq!(container.text_content().unwrap(), "failed");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_failures_while_patching_the_subtree_show_the_fallback_until_retried() {
+        let failing = Rc::new(Cell::new(false));
+        let retry: Rc<RefCell<Option<Retry>>> = Rc::default();
+        let view = || {
+            let (failing, retry) = (failing.clone(), retry.clone());
+            rsx!(div {
+                error_boundary(
+                    move || Ok(match failing.get() {
+                        true => broken("child"),
+                        false => rsx!(p { "child" }),
+                    }),
+                    move |_, handle| {
+                        *retry.borrow_mut() = Some(handle);
+                        rsx!(em { "failed" })
+                    },
+                )
+            })
+        };
+
+        let container = vdom::mount(view());
+        assert_eq!(container.text_content().unwrap(), "child");
+
+        failing.set(true);
+        vdom::render(&container, view());
+        assert_eq!(container.text_content().unwrap(), "failed");
+
+        failing.set(false);
+        retry.borrow().as_ref().unwrap().retry();
+        flush_sync();
+        assert_eq!(container.text_content().unwrap(), "child");
+    }
+}
@@ -0,0 +1,94 @@
+use super::VNode;
+
+/// What `rsx!` passes to a component called with a block,
+/// `tooltip(position) { ... }`: the children in the block, and the items of
+/// each `name: { ... }` in it as the slot `name`. They can be placed only
+/// once, so only functions take them; `rsx!` rejects a block after
+/// `component::<C>(props)`.
+#[derive(Default)]
+pub struct Children {
+    nodes: VNode,
+    slots: Vec<(&'static str, VNode)>,
+}
+
+impl Children {
+    pub fn new(nodes: impl Into<VNode>) -> Self {
+        Self { nodes: nodes.into(), slots: Vec::new() }
+    }
+
+    pub fn with_slot(mut self, name: &'static str, nodes: impl Into<VNode>) -> Self {
+        self.slots.push((name, nodes.into()));
+        self
+    }
+
+    /// Takes what the call site put in the slot `name`, if it filled it.
+    pub fn slot(&mut self, name: &str) -> Option<VNode> {
+        let index = self.slots.iter().position(|(slot, _)| *slot == name)?;
+        Some(self.slots.swap_remove(index).1)
+    }
+}
+
+impl From<VNode> for Children {
+    fn from(nodes: VNode) -> Self {
+        Self::new(nodes)
+    }
+}
+
+// `rsx!(div { children })` adds the children outside any slot
+impl From<Children> for VNode {
+    fn from(children: Children) -> Self {
+        children.nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rsx, vdom};
+
+    fn card(title: &str, mut children: Children) -> VNode {
+        let header = children.slot("header");
+        let footer = children.slot("footer");
+        rsx!(section {
+            class = "card",
+            h2 { @title },
+            if let Some(header) = header {
+                header { header }
+            },
+            div { class = "card-body", children },
+            if let Some(footer) = footer {
+                footer { footer }
+            }
+        })
+    }
+
+    #[test]
+    fn test_calls_with_a_block_get_children_and_slots() {
+        let tags = ["rust", "wasm"];
+        let html = vdom::render_to_string(rsx!(div {
+            card("Bolt") {
+                footer: { "by ", b { "us" } },
+                p { "first" },
+                for tag in &tags {
+                    span { @tag }
+                },
+            }
+        }));
+
+        assert_eq!(
+            html,
+            "<div><section class=\"card\"><h2>Bolt</h2><!----><div class=\"card-body\"><p>first</p>\
+             <span>rust</span><span>wasm</span><!----></div><footer>by <b>us</b></footer><!----></section>\
+             </div>"
+        );
+    }
+
+    #[test]
+    fn test_slots_are_taken_once() {
+        let mut children = Children::new(rsx!(p { })).with_slot("header", rsx!(h1 { }));
+
+        assert!(children.slot("header").is_some());
+        assert!(children.slot("header").is_none());
+        assert!(children.slot("footer").is_none());
+    }
+}
@@ -0,0 +1,199 @@
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use web_sys::Node;
+
+use super::context::{with_context, Context};
+use super::hooks::{with_hooks, Hooks};
+use super::VNode;
+
+/// A piece of UI that lives across renders. The instance `create` returns is
+/// kept while the component stays in the tree: parent renders hand it new
+/// props and re-render it instead of building a new one.
+///
+/// The renderer calls `mounted` once the component's nodes are in the
+/// document, `updated` after a parent render patched it, and
+/// `before_unmount` before its nodes are removed. That's where timers,
+/// subscriptions and focus handling go.
+pub trait Component: 'static {
+    type Props: 'static;
+
+    fn create(props: &Self::Props) -> Self
+    where
+        Self: Sized;
+
+    fn render(&self, props: &Self::Props) -> VNode;
+
+    fn mounted(&mut self, _props: &Self::Props) {}
+
+    fn updated(&mut self, _props: &Self::Props) {}
+
+    fn before_unmount(&mut self) {}
+}
+
+/// Places a `C` in the tree: `rsx!(div { component::<TodoInput>(()) })`.
+pub fn component<C: Component>(props: C::Props) -> VNode {
+    VNode::Component(Box::new(VComponent {
+        type_id: TypeId::of::<C>(),
+        props: Some(Box::new(props)),
+        create: create_instance::<C>,
+        state: None,
+    }))
+}
+
+fn create_instance<C: Component>(props: Box<dyn Any>) -> Box<dyn AnyComponent> {
+    let props = *props.downcast::<C::Props>().expect("props match their component");
+    Box::new(Instance { component: C::create(&props), props })
+}
+
+// `Component` with its props, behind a type the renderer can store
+pub(super) trait AnyComponent {
+    fn set_props(&mut self, props: Box<dyn Any>);
+    fn render(&self) -> VNode;
+    fn mounted(&mut self);
+    fn updated(&mut self);
+    fn before_unmount(&mut self);
+}
+
+struct Instance<C: Component> {
+    component: C,
+    props: C::Props,
+}
+
+impl<C: Component> AnyComponent for Instance<C> {
+    fn set_props(&mut self, props: Box<dyn Any>) {
+        self.props = *props.downcast::<C::Props>().expect("props match their component");
+    }
+
+    fn render(&self) -> VNode {
+        self.component.render(&self.props)
+    }
+
+    fn mounted(&mut self) {
+        self.component.mounted(&self.props);
+    }
+
+    fn updated(&mut self) {
+        self.component.updated(&self.props);
+    }
+
+    fn before_unmount(&mut self) {
+        self.component.before_unmount();
+    }
+}
+
+pub struct VComponent {
+    pub(super) type_id: TypeId,
+    // Taken when the component is created or patched
+    pub(super) props: Option<Box<dyn Any>>,
+    pub(super) create: fn(Box<dyn Any>) -> Box<dyn AnyComponent>,
+    pub(super) state: Option<Rc<ComponentState>>,
+}
+
+/// A mounted component: its instance and what it rendered, which sits in
+/// front of `marker`.
+pub(super) struct ComponentState {
+    pub(super) instance: RefCell<Box<dyn AnyComponent>>,
+    pub(super) output: RefCell<Vec<VNode>>,
+    pub(super) marker: Node,
+    pub(super) hooks: Rc<Hooks>,
+    // What its ancestors provided, as of the last time it was patched
+    pub(super) context: RefCell<Context>,
+    // Set while a re-render for a hook update is scheduled
+    pub(super) dirty: Cell<bool>,
+}
+
+impl ComponentState {
+    /// Renders the instance, returning its output and the context its
+    /// output is patched in.
+    pub(super) fn render(self: &Rc<Self>) -> (Vec<VNode>, Context) {
+        let mut output = Vec::new();
+        let context = self.context.borrow().clone();
+        let (node, context) = with_context(context, || {
+            with_hooks(&self.hooks, Rc::downgrade(self), || self.instance.borrow().render())
+        });
+        node.flatten_into(&mut output);
+        (output, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rsx, vdom};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    struct Labelled {
+        renders: usize,
+    }
+
+    impl Component for Labelled {
+        type Props = (Log, &'static str);
+
+        fn create(_props: &Self::Props) -> Self {
+            Self { renders: 0 }
+        }
+
+        fn render(&self, (_, label): &Self::Props) -> VNode {
+            rsx!(fragment { b { @label }, i { @self.renders } })
+        }
+
+        fn mounted(&mut self, (log, label): &Self::Props) {
+            log.borrow_mut().push(format!("mounted {}", label));
+        }
+
+        fn updated(&mut self, (log, label): &Self::Props) {
+            self.renders += 1;
+            log.borrow_mut().push(format!("updated {}", label));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_renderer_calls_lifecycle_hooks() {
+        let log = Log::default();
+        let view = |label| rsx!(div { p { "before" }, component::<Labelled>((log.clone(), label)) });
+
+        let container = vdom::mount(view("a"));
+        let label = container.query_selector("b").unwrap().unwrap();
+        vdom::render(&container, view("b"));
+
+        assert_eq!(*log.borrow(), ["mounted a", "updated b"]);
+        assert!(label.is_same_node(container.query_selector("b").unwrap().as_deref()));
+        assert_eq!(container.text_content().unwrap(), "beforeb0");
+
+        vdom::render(&container, rsx!(div { }));
+        assert_eq!(container.inner_html(), "<div></div>");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_components_unmount_with_their_ancestors() {
+        struct Tracked(Log);
+
+        impl Component for Tracked {
+            type Props = Log;
+
+            fn create(log: &Log) -> Self {
+                Tracked(log.clone())
+            }
+
+            fn render(&self, _: &Log) -> VNode {
+                rsx!(span { "tracked" })
+            }
+
+            fn before_unmount(&mut self) {
+                self.0.borrow_mut().push("before_unmount".to_string());
+            }
+        }
+
+        let log = Log::default();
+        let container = vdom::mount(rsx!(section { div { component::<Tracked>(log.clone()) } }));
+        vdom::unmount(&container);
+
+        assert_eq!(*log.borrow(), ["before_unmount"]);
+        assert_eq!(container.inner_html(), "");
+    }
+}
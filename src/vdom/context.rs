@@ -0,0 +1,179 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::hooks::is_rendering;
+
+/// The values a component can look up with `use_context`, innermost first.
+#[derive(Clone, Default)]
+pub(super) struct Context(Option<Rc<Provided>>);
+
+struct Provided {
+    type_id: TypeId,
+    value: Rc<dyn Any>,
+    parent: Context,
+}
+
+thread_local! {
+    // What the component rendering right now and the components it creates see
+    static SCOPE: RefCell<Context> = RefCell::default();
+    // Provided outside any component, so every tree sees it
+    static ROOT: RefCell<HashMap<TypeId, Rc<dyn Any>>> = RefCell::default();
+}
+
+impl Context {
+    pub(super) fn current() -> Self {
+        SCOPE.with(|scope| scope.borrow().clone())
+    }
+
+    fn find(&self, type_id: TypeId) -> Option<Rc<dyn Any>> {
+        let mut context = self;
+        while let Some(provided) = &context.0 {
+            if provided.type_id == type_id {
+                return Some(provided.value.clone());
+            }
+            context = &provided.parent;
+        }
+        None
+    }
+}
+
+/// Runs `f` in `context` and returns what it returned along with the context
+/// it left, including whatever it provided.
+pub(super) fn with_context<R>(context: Context, f: impl FnOnce() -> R) -> (R, Context) {
+    let previous = SCOPE.with(|scope| scope.replace(context));
+    let output = f();
+    let context = SCOPE.with(|scope| scope.replace(previous));
+    (output, context)
+}
+
+/// Makes `value` what `use_context::<T>()` returns in the rest of this
+/// component's render and in the components it renders, down to one that
+/// provides another `T`. Like a hook, it's called on every render.
+///
+/// Called outside a component, e.g. in `main()` or a test, it provides
+/// `value` to every tree that doesn't provide its own `T`.
+pub fn provide_context<T: 'static>(value: T) {
+    let type_id = TypeId::of::<T>();
+    let value: Rc<dyn Any> = Rc::new(value);
+    if !is_rendering() {
+        ROOT.with(|root| root.borrow_mut().insert(type_id, value));
+        return;
+    }
+    SCOPE.with(|scope| {
+        let mut scope = scope.borrow_mut();
+        let parent = scope.clone();
+        *scope = Context(Some(Rc::new(Provided { type_id, value, parent })));
+    });
+}
+
+/// Runs `f` with its own copy of the values provided outside components,
+/// so what it provides that way, like a test's stand-in services, is gone
+/// once it returns and the values from before are back. Trees mounted in
+/// `f` that render again afterwards see those values, not `f`'s.
+pub fn with_root_context<R>(f: impl FnOnce() -> R) -> R {
+    let saved = ROOT.with(|root| root.borrow().clone());
+    let output = f();
+    ROOT.with(|root| *root.borrow_mut() = saved);
+    output
+}
+
+/// The `T` the nearest ancestor component provided, or the one provided
+/// outside any component.
+pub fn use_context<T: Clone + 'static>() -> Option<T> {
+    let type_id = TypeId::of::<T>();
+    let value = Context::current()
+        .find(type_id)
+        .or_else(|| ROOT.with(|root| root.borrow().get(&type_id).cloned()))?;
+    value.downcast_ref::<T>().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::flush_sync;
+    use crate::vdom::{self, component, use_state, Component, State, VNode};
+    use crate::rsx;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[derive(Clone)]
+    struct Greeting(&'static str);
+
+    thread_local! {
+        static SETTER: RefCell<Option<State<u32>>> = const { RefCell::new(None) };
+    }
+
+    // Renders the greeting it sees, then its children
+    struct Greet;
+
+    impl Component for Greet {
+        type Props = (Option<&'static str>, Option<Box<dyn Fn() -> VNode>>);
+
+        fn create(_: &Self::Props) -> Self {
+            Greet
+        }
+
+        fn render(&self, (provide, children): &Self::Props) -> VNode {
+            if let Some(greeting) = provide {
+                provide_context(Greeting(greeting));
+            }
+            let renders = use_state(|| 0);
+            SETTER.with(|setter| *setter.borrow_mut() = Some(renders.clone()));
+            let greeting = use_context::<Greeting>().map_or("none", |greeting| greeting.0);
+            rsx!(fragment {
+                b { @greeting },
+                if let Some(children) = children { children() }
+            })
+        }
+    }
+
+    fn greet(provide: Option<&'static str>, children: Option<Box<dyn Fn() -> VNode>>) -> VNode {
+        component::<Greet>((provide, children))
+    }
+
+    #[wasm_bindgen_test]
+    fn test_nearest_provider_wins() {
+        let container = vdom::mount(rsx!(div {
+            greet(Some("outer"), Some(Box::new(|| rsx!(fragment {
+                greet(None, None),
+                greet(Some("inner"), Some(Box::new(|| greet(None, None))))
+            }))))
+        }));
+
+        assert_eq!(container.text_content().unwrap(), "outerouterinnerinner");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_components_keep_their_context_when_rendering_alone() {
+        let container = vdom::mount(rsx!(div {
+            greet(Some("hello"), Some(Box::new(|| greet(None, None))))
+        }));
+
+        // The last `Greet` rendered is the inner one
+        SETTER.with(|setter| setter.borrow().clone().unwrap()).set(1);
+        flush_sync();
+
+        assert_eq!(container.text_content().unwrap(), "hellohello");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_values_provided_outside_components_reach_every_tree() {
+        #[derive(Clone)]
+        struct Service(u32);
+
+        let container = with_root_context(|| {
+            provide_context(Service(7));
+            vdom::mount(rsx!(div {
+                greet(Some("unrelated"), Some(Box::new(|| rsx!(span {
+                    @use_context::<Service>().map_or(0, |service| service.0)
+                }))))
+            }))
+        });
+
+        assert_eq!(container.text_content().unwrap(), "unrelated7");
+        assert!(use_context::<Service>().is_none());
+    }
+}
@@ -0,0 +1,328 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use super::component::ComponentState;
+use super::patch::{schedule_update, update_now};
+
+/// Values of `use_state`, `use_reducer` and `use_memo`, in call order.
+#[derive(Default)]
+pub(super) struct Hooks {
+    values: RefCell<Vec<Slot>>,
+}
+
+struct Slot {
+    value: Rc<dyn Any>,
+    // Slots taken by hooks its initializer called, which later renders skip
+    nested: usize,
+}
+
+// The component rendering right now
+struct Rendering {
+    hooks: Rc<Hooks>,
+    // Dangling when rendering to a string: nothing re-renders then
+    component: Weak<ComponentState>,
+    next: usize,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rendering>> = const { RefCell::new(None) };
+}
+
+pub(super) fn with_hooks<R>(hooks: &Rc<Hooks>, component: Weak<ComponentState>, render: impl FnOnce() -> R) -> R {
+    let rendering = Rendering { hooks: hooks.clone(), component, next: 0 };
+    let previous = CURRENT.with(|current| current.replace(Some(rendering)));
+    let output = render();
+    CURRENT.with(|current| *current.borrow_mut() = previous);
+    output
+}
+
+pub(super) fn is_rendering() -> bool {
+    CURRENT.with(|current| current.borrow().is_some())
+}
+
+// The value of the next hook of the rendering component, created by `init`
+// on its first render
+fn hook<T: 'static>(init: impl FnOnce() -> T) -> (Rc<T>, Weak<ComponentState>) {
+    // The slot is claimed before `init` runs and nothing stays borrowed
+    // meanwhile, so hooks `init` calls itself take the slots after it
+    let (hooks, index, component, value) = CURRENT.with(|current| {
+        let mut current = current.borrow_mut();
+        let rendering = current
+            .as_mut()
+            .expect("hooks can only be used while a component renders");
+        let index = rendering.next;
+        let mut values = rendering.hooks.values.borrow_mut();
+        let value = match values.get(index) {
+            Some(slot) => {
+                rendering.next += 1 + slot.nested;
+                Some(slot.value.clone())
+            }
+            None => {
+                assert_eq!(index, values.len(), "hooks must be called in the same order on every render");
+                values.push(Slot { value: Rc::new(()), nested: 0 });
+                rendering.next += 1;
+                None
+            }
+        };
+        (rendering.hooks.clone(), index, rendering.component.clone(), value)
+    });
+
+    let value = value.unwrap_or_else(|| {
+        let value: Rc<dyn Any> = Rc::new(init());
+        let mut values = hooks.values.borrow_mut();
+        let nested = values.len() - index - 1;
+        values[index] = Slot { value: value.clone(), nested };
+        value
+    });
+    let value = value
+        .downcast::<T>()
+        .expect("hooks must be called in the same order on every render");
+    (value, component)
+}
+
+/// State owned by a component instance. Setting it re-renders that
+/// component alone, on the next frame.
+pub struct State<T> {
+    value: Rc<RefCell<T>>,
+    component: Weak<ComponentState>,
+}
+
+/// Returns the component's state, created by `init` on its first render.
+/// It lives as long as the instance does, across parent renders.
+pub fn use_state<T: 'static>(init: impl FnOnce() -> T) -> State<T> {
+    let (value, component) = hook(|| RefCell::new(init()));
+    State { value, component }
+}
+
+impl<T> State<T> {
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.value.borrow())
+    }
+
+    pub fn set(&self, value: T) {
+        *self.value.borrow_mut() = value;
+        schedule_update(&self.component);
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.value.borrow_mut());
+        schedule_update(&self.component);
+    }
+
+    // Like `set`, but the component re-renders in the patch in progress,
+    // once the part running now is done
+    pub(super) fn set_now(&self, value: T) {
+        *self.value.borrow_mut() = value;
+        update_now(&self.component);
+    }
+}
+
+impl<T: Clone> State<T> {
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self { value: self.value.clone(), component: self.component.clone() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&*self.value.borrow()).finish()
+    }
+}
+
+/// Component state changed through actions, like the app's `dispatch`.
+pub struct Reducer<S, A> {
+    state: State<S>,
+    reducer: fn(&S, A) -> S,
+}
+
+/// Returns the component's state, created by `init` on its first render and
+/// replaced by `reducer` on every `dispatch`.
+pub fn use_reducer<S: 'static, A>(reducer: fn(&S, A) -> S, init: impl FnOnce() -> S) -> Reducer<S, A> {
+    Reducer { state: use_state(init), reducer }
+}
+
+impl<S, A> Reducer<S, A> {
+    pub fn with<R>(&self, f: impl FnOnce(&S) -> R) -> R {
+        self.state.with(f)
+    }
+
+    pub fn dispatch(&self, action: A) {
+        let next = self.state.with(|state| (self.reducer)(state, action));
+        self.state.set(next);
+    }
+}
+
+impl<S: Clone, A> Reducer<S, A> {
+    pub fn get(&self) -> S {
+        self.state.get()
+    }
+}
+
+impl<S, A> Clone for Reducer<S, A> {
+    fn clone(&self) -> Self {
+        Self { state: self.state.clone(), reducer: self.reducer }
+    }
+}
+
+/// Computes a value once and again only when `deps` differ from the previous
+/// render's.
+pub fn use_memo<D, T>(deps: D, compute: impl FnOnce(&D) -> T) -> T
+where
+    D: PartialEq + 'static,
+    T: Clone + 'static,
+{
+    let (memo, _) = hook(|| RefCell::new(None::<(D, T)>));
+    let mut memo = memo.borrow_mut();
+    if memo.as_ref().is_none_or(|(previous, _)| *previous != deps) {
+        let value = compute(&deps);
+        *memo = Some((deps, value));
+    }
+    memo.as_ref().unwrap().1.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::flush_sync;
+    use crate::vdom::{self, component, Component, VNode};
+    use crate::rsx;
+    use std::cell::Cell;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    thread_local! {
+        static COUNTER: RefCell<Option<State<i32>>> = const { RefCell::new(None) };
+    }
+
+    struct Counter;
+
+    impl Component for Counter {
+        type Props = Rc<Cell<usize>>;
+
+        fn create(_: &Self::Props) -> Self {
+            Counter
+        }
+
+        fn render(&self, renders: &Self::Props) -> VNode {
+            renders.set(renders.get() + 1);
+            let count = use_state(|| 0);
+            COUNTER.with(|counter| *counter.borrow_mut() = Some(count.clone()));
+            rsx!(span { @count.get() })
+        }
+    }
+
+    fn counter() -> State<i32> {
+        COUNTER.with(|counter| counter.borrow().clone().unwrap())
+    }
+
+    #[wasm_bindgen_test]
+    fn test_state_rerenders_only_its_component() {
+        let renders = Rc::new(Cell::new(0));
+        let parent_renders = Rc::new(Cell::new(0));
+        let view = || {
+            parent_renders.set(parent_renders.get() + 1);
+            rsx!(div { p { "parent" }, component::<Counter>(renders.clone()) })
+        };
+        let container = vdom::mount(view());
+
+        counter().set(5);
+        flush_sync();
+
+        assert_eq!((renders.get(), parent_renders.get()), (2, 1));
+        assert_eq!(container.text_content().unwrap(), "parent5");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_state_survives_parent_renders() {
+        let renders = Rc::new(Cell::new(0));
+        let view = |title: &'static str| rsx!(div { h1 { @title }, component::<Counter>(renders.clone()) });
+        let container = vdom::mount(view("a"));
+
+        counter().update(|count| *count += 2);
+        vdom::render(&container, view("b"));
+        // The parent render already showed the new count
+        flush_sync();
+
+        assert_eq!(renders.get(), 2);
+        assert_eq!(container.text_content().unwrap(), "b2");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_reducers_and_memos() {
+        struct Steps;
+
+        thread_local! {
+            static STEPS: RefCell<Option<Reducer<Vec<u32>, u32>>> = const { RefCell::new(None) };
+        }
+
+        impl Component for Steps {
+            type Props = Rc<Cell<usize>>;
+
+            fn create(_: &Self::Props) -> Self {
+                Steps
+            }
+
+            fn render(&self, computed: &Self::Props) -> VNode {
+                let steps = use_reducer(
+                    |steps: &Vec<u32>, step| steps.iter().copied().chain([step]).collect(),
+                    Vec::new,
+                );
+                let total = use_memo(steps.get(), |steps| {
+                    computed.set(computed.get() + 1);
+                    steps.iter().sum::<u32>()
+                });
+                STEPS.with(|slot| *slot.borrow_mut() = Some(steps));
+                rsx!(span { @total })
+            }
+        }
+
+        let computed = Rc::new(Cell::new(0));
+        let view = || rsx!(div { component::<Steps>(computed.clone()) });
+        let container = vdom::mount(view());
+        let steps = STEPS.with(|slot| slot.borrow().clone().unwrap());
+
+        steps.dispatch(2);
+        steps.dispatch(3);
+        flush_sync();
+        assert_eq!(container.text_content().unwrap(), "5");
+
+        // Same deps: the memo keeps its value
+        vdom::render(&container, view());
+        assert_eq!(computed.get(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_initializers_can_use_hooks() {
+        struct Nested;
+
+        impl Component for Nested {
+            type Props = ();
+
+            fn create(_: &()) -> Self {
+                Nested
+            }
+
+            fn render(&self, _: &()) -> VNode {
+                let doubled = use_state(|| use_memo(21, |n| n * 2));
+                let label = use_state(|| "answer");
+                rsx!(span { @label.get(), ": ", @doubled.get() })
+            }
+        }
+
+        let view = || rsx!(div { component::<Nested>(()) });
+        let container = vdom::mount(view());
+        // The nested memo isn't called again; `label` still finds its slot
+        vdom::render(&container, view());
+
+        assert_eq!(container.text_content().unwrap(), "answer: 42");
+    }
+}
@@ -0,0 +1,393 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Node, Text};
+
+use super::boundary::catch;
+use super::component::ComponentState;
+use super::context::{with_context, Context};
+use super::hooks::with_hooks;
+use super::patch::{is_root, open_portal, patch_items, patch_text, queue_mounted};
+use super::ssr::rendered_attributes;
+use super::{ElementItems, Hole, HoleContent, StaticElement, StaticNode, VNode};
+use crate::rsx::{create_element, style, Namespace};
+use crate::utils::get_document;
+
+/// Claims the children of a container passed to `hydrate` for `children`.
+/// Whatever follows them is left alone: a container like `<body>` also
+/// holds scripts and nodes added by other code.
+pub(super) fn adopt_root(container: &Node, children: Vec<VNode>) -> Vec<VNode> {
+    let mut cursor = container.first_child();
+    children.into_iter().map(|child| adopt(container, child, &mut cursor)).collect()
+}
+
+/// Claims the children of `parent` for `children` in order, fixing up and
+/// warning about whatever doesn't match.
+fn adopt_children(parent: &Node, children: Vec<VNode>) -> Vec<VNode> {
+    let mut cursor = parent.first_child();
+    let mounted = children.into_iter().map(|child| adopt(parent, child, &mut cursor)).collect();
+    remove_rest(parent, cursor);
+    mounted
+}
+
+/// Does what `create` does for `node`, except it takes over the DOM at
+/// `cursor` instead of building it, and moves `cursor` past it.
+fn adopt(parent: &Node, node: VNode, cursor: &mut Option<Node>) -> VNode {
+    match node {
+        VNode::Element(mut element) => {
+            let elem = claim_element(parent, cursor, element.ns, element.tag);
+            remove_extras(&elem, &[], &element.items);
+            patch_items(&elem, ElementItems::default(), &mut element.items);
+            // An element rendered into on its own, like #app, keeps the
+            // children its own root claimed
+            if !(element.children.is_empty() && is_root(&elem)) {
+                element.children = adopt_children(&elem, mem::take(&mut element.children));
+            }
+            element.node = Some(elem);
+            VNode::Element(element)
+        }
+        VNode::Template(mut template) => {
+            let skeleton = template.skeleton;
+            let root = claim_element(parent, cursor, skeleton.ns, skeleton.tag);
+            adopt_static(&root, skeleton, &mut Vec::new(), &mut template.holes);
+            template.root = Some(root);
+            VNode::Template(template)
+        }
+        VNode::Text(mut text) => {
+            let node = claim_text(parent, cursor, &text.text);
+            patch_text(&node, None, &mut text);
+            text.node = Some(node);
+            VNode::Text(text)
+        }
+        VNode::Component(mut component) => {
+            let instance = (component.create)(component.props.take().unwrap());
+            // The marker comes after the output, so the state is built once
+            // both are claimed; hooks only need its address meanwhile
+            let state = Rc::new_cyclic(|weak| {
+                let hooks = Rc::default();
+                let context = Context::current();
+                let mut output = Vec::new();
+                let (node, provided) =
+                    with_context(context.clone(), || with_hooks(&hooks, weak.clone(), || instance.render()));
+                node.flatten_into(&mut output);
+                let (output, _) = with_context(provided, || {
+                    output.into_iter().map(|node| adopt(parent, node, cursor)).collect()
+                });
+                ComponentState {
+                    instance: RefCell::new(instance),
+                    output: RefCell::new(output),
+                    marker: claim_comment(parent, cursor),
+                    hooks,
+                    context: RefCell::new(context),
+                    dirty: Cell::new(false),
+                }
+            });
+            queue_mounted(&state);
+            component.state = Some(state);
+            VNode::Component(component)
+        }
+        VNode::Portal(mut portal) => {
+            portal.placeholder = Some(claim_comment(parent, cursor));
+            open_portal(&mut portal);
+            VNode::Portal(portal)
+        }
+        VNode::Fragment(_) => unreachable!("fragments are flattened before mounting"),
+    }
+}
+
+/// Claims the children of `elem` for the static nodes of `element`,
+/// resolving the holes on the way.
+fn adopt_static(elem: &Element, element: &StaticElement, path: &mut Vec<u32>, holes: &mut [Hole]) {
+    let hole = holes.iter_mut().find(|hole| hole.path == path.as_slice());
+    let items = match hole {
+        Some(Hole { content: HoleContent::Element(items), node, .. }) => {
+            *node = Some(elem.clone().into());
+            Some(items)
+        }
+        _ => None,
+    };
+    remove_extras(elem, element.attributes, items.as_deref().unwrap_or(&ElementItems::default()));
+    for (name, value) in element.attributes {
+        if elem.get_attribute(name).as_deref() != Some(*value) {
+            catch(elem.set_attribute(name, value));
+        }
+    }
+    if let Some(items) = items {
+        patch_items(elem, ElementItems::default(), items);
+    }
+
+    let mut cursor = elem.first_child();
+    for (index, child) in element.children.iter().enumerate() {
+        path.push(index as u32);
+        let hole = holes.iter_mut().find(|hole| hole.path == path.as_slice());
+        match (child, hole) {
+            (StaticNode::Element(child), _) => {
+                let node = claim_element(elem, &mut cursor, child.ns, child.tag);
+                adopt_static(&node, child, path, holes);
+            }
+            (StaticNode::Text(_), Some(Hole { content: HoleContent::Text(text), node, .. })) => {
+                let text_node = claim_text(elem, &mut cursor, &text.text);
+                patch_text(&text_node, None, text);
+                *node = Some(text_node.into());
+            }
+            (StaticNode::Text(text), _) => {
+                claim_text(elem, &mut cursor, text);
+            }
+            (StaticNode::Marker, Some(Hole { content: HoleContent::Children(children), node, .. })) => {
+                *children = mem::take(children).into_iter().map(|child| adopt(elem, child, &mut cursor)).collect();
+                *node = Some(claim_comment(elem, &mut cursor));
+            }
+            (StaticNode::Marker, _) => {
+                claim_comment(elem, &mut cursor);
+            }
+        }
+        path.pop();
+    }
+    remove_rest(elem, cursor);
+}
+
+fn claim_element(parent: &Node, cursor: &mut Option<Node>, ns: Namespace, tag: &'static str) -> Element {
+    skip_blank(parent, cursor);
+    let matches = |node: &Node| node.dyn_ref::<Element>().is_some_and(|elem| elem.local_name() == tag);
+    claim(parent, cursor, &format!("<{}>", tag), matches, || create_element(ns, tag).into()).unchecked_into()
+}
+
+fn claim_comment(parent: &Node, cursor: &mut Option<Node>) -> Node {
+    skip_blank(parent, cursor);
+    let matches = |node: &Node| node.node_type() == Node::COMMENT_NODE;
+    claim(parent, cursor, "a comment", matches, || get_document().create_comment("").into())
+}
+
+// Takes the node at `cursor` if `matches` accepts it, or puts a new one in
+// its place
+fn claim(
+    parent: &Node,
+    cursor: &mut Option<Node>,
+    expected: &str,
+    matches: impl Fn(&Node) -> bool,
+    build: impl FnOnce() -> Node,
+) -> Node {
+    let node = match cursor.take() {
+        Some(node) if matches(&node) => node,
+        found => {
+            warn_mismatch(expected, found.as_ref());
+            let node = build();
+            match &found {
+                Some(found) => catch(parent.replace_child(&node, found)),
+                None => catch(parent.append_child(&node)),
+            };
+            node
+        }
+    };
+    *cursor = node.next_sibling();
+    node
+}
+
+/// Claims a text node showing `text`. Neighbouring text nodes come back
+/// from HTML as one and empty ones not at all, so those are split off and
+/// created without a warning.
+fn claim_text(parent: &Node, cursor: &mut Option<Node>, text: &str) -> Text {
+    let found = cursor.as_ref().and_then(|node| node.dyn_ref::<Text>()).cloned();
+    match found {
+        _ if text.is_empty() => insert_text(parent, cursor, text),
+        Some(node) => {
+            let data = node.data();
+            if data.len() > text.len() && data.starts_with(text) {
+                let rest = catch(node.split_text(text.encode_utf16().count() as u32));
+                *cursor = match rest {
+                    Some(rest) => Some(rest.into()),
+                    None => node.next_sibling(),
+                };
+            } else {
+                if data != text {
+                    warn_mismatch(&format!("text {:?}", text), cursor.as_ref());
+                    node.set_data(text);
+                }
+                *cursor = node.next_sibling();
+            }
+            node
+        }
+        None => {
+            warn_mismatch(&format!("text {:?}", text), cursor.as_ref());
+            insert_text(parent, cursor, text)
+        }
+    }
+}
+
+fn insert_text(parent: &Node, cursor: &Option<Node>, text: &str) -> Text {
+    let node = get_document().create_text_node(text);
+    catch(parent.insert_before(&node, cursor.as_ref()));
+    node
+}
+
+// Formatting whitespace around server-rendered elements
+fn skip_blank(parent: &Node, cursor: &mut Option<Node>) {
+    while let Some(node) = cursor.clone().filter(is_blank) {
+        *cursor = node.next_sibling();
+        catch(parent.remove_child(&node));
+    }
+}
+
+fn is_blank(node: &Node) -> bool {
+    node.dyn_ref::<Text>().is_some_and(|text| text.data().trim().is_empty())
+}
+
+fn remove_rest(parent: &Node, mut cursor: Option<Node>) {
+    while let Some(node) = cursor {
+        cursor = node.next_sibling();
+        if !is_blank(&node) {
+            warn_mismatch("nothing", Some(&node));
+        }
+        catch(parent.remove_child(&node));
+    }
+}
+
+/// Removes the attributes, classes and inline styles the server put on
+/// `elem` that rendering `attributes` and `items` wouldn't, warning about
+/// each. What is missing or different is set by `patch_items` afterwards.
+fn remove_extras(elem: &Element, attributes: &[(&'static str, &'static str)], items: &ElementItems) {
+    let expected = rendered_attributes(attributes, items);
+    let expected = |name: &str| expected.iter().find(|(expected, _)| *expected == name).map(|(_, value)| value.to_string());
+
+    for name in elem.get_attribute_names().iter().filter_map(|name| name.as_string()) {
+        if name != "class" && name != "style" && expected(&name).is_none() {
+            warn_extra(elem, &format!("attribute `{}`", name));
+            catch(elem.remove_attribute(&name));
+        }
+    }
+
+    let classes = expected("class").unwrap_or_default();
+    let class_list = elem.class_list();
+    let found: Vec<String> = (0..class_list.length()).filter_map(|index| class_list.item(index)).collect();
+    for class in found {
+        if !classes.split_whitespace().any(|expected| expected == class) {
+            warn_extra(elem, &format!("class `{}`", class));
+            catch(class_list.remove_1(&class));
+        }
+    }
+
+    let styles = expected("style").unwrap_or_default();
+    let declarations = style(elem);
+    let found: Vec<String> = (0..declarations.length()).map(|index| declarations.item(index)).collect();
+    for property in found {
+        let declared = styles.split(';').filter_map(|declaration| declaration.split_once(':'));
+        if !declared.map(|(expected, _)| expected.trim()).any(|expected| expected == property) {
+            warn_extra(elem, &format!("style `{}`", property));
+            catch(declarations.remove_property(&property));
+        }
+    }
+
+    // Left empty by the removals above
+    for name in ["class", "style"] {
+        if expected(name).is_none() && elem.has_attribute(name) {
+            catch(elem.remove_attribute(name));
+        }
+    }
+}
+
+fn warn_extra(elem: &Element, extra: &str) {
+    web_sys::console::warn_1(
+        &format!("hydration mismatch: <{}> has {} the client doesn't render", elem.local_name(), extra).into(),
+    );
+}
+
+fn warn_mismatch(expected: &str, found: Option<&Node>) {
+    let found = match found {
+        Some(node) => match node.dyn_ref::<Element>() {
+            Some(elem) => format!("<{}>", elem.local_name()),
+            None if node.node_type() == Node::TEXT_NODE => format!("text {:?}", node.text_content().unwrap_or_default()),
+            None => "a comment".to_string(),
+        },
+        None => "nothing".to_string(),
+    };
+    web_sys::console::warn_1(&format!("hydration mismatch: expected {}, found {}", expected, found).into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsx;
+    use crate::signals::Signal;
+    use crate::vdom::{self, render_to_string};
+    use wasm_bindgen_test::*;
+    use web_sys::HtmlElement;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn server_rendered(node: VNode) -> Element {
+        let container = create_element(Namespace::Html, "div");
+        container.set_inner_html(&render_to_string(node));
+        container
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hydration_keeps_server_nodes() {
+        let clicks = Rc::new(Cell::new(0));
+        let count = Signal::new(1);
+        let view = || {
+            let clicks = clicks.clone();
+            rsx!(div {
+                button { class = "add", click => move |_| clicks.set(clicks.get() + 1), "Add" },
+                "Count: ", @count,
+                for item in ["a", "b"] { span { key = item, @item } }
+            })
+        };
+        let container = server_rendered(view());
+        let button = container.query_selector(".add").unwrap().unwrap();
+        let span = container.query_selector("span").unwrap().unwrap();
+
+        vdom::hydrate(&container, view());
+        button.unchecked_ref::<HtmlElement>().click();
+        count.set(2);
+
+        assert_eq!(clicks.get(), 1);
+        assert!(button.is_same_node(container.query_selector(".add").unwrap().as_deref()));
+        assert!(span.is_same_node(container.query_selector("span").unwrap().as_deref()));
+        assert_eq!(container.text_content().unwrap(), "AddCount: 2ab");
+
+        // Later renders patch the hydrated tree like any other
+        vdom::render(&container, rsx!(div { p { "after" } }));
+        assert_eq!(container.inner_html(), "<div><p>after</p></div>");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_mismatches_are_fixed_up() {
+        let container = create_element(Namespace::Html, "div");
+        container.set_inner_html("<div><span>old</span><em>extra</em></div>");
+
+        vdom::hydrate(&container, rsx!(div { p { "new" } }));
+
+        assert_eq!(container.inner_html(), "<div><p>new</p></div>");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_server_only_attributes_are_removed() {
+        let container = create_element(Namespace::Html, "div");
+        container.set_inner_html(
+            "<p id=\"old\" title=\"kept\" class=\"a stale\" style=\"color: red; margin: 0px;\">x</p>\
+             <span data-stale=\"\" class=\"b\">y</span>",
+        );
+        let color = "red";
+
+        vdom::hydrate(&container, rsx!(fragment {
+            p { title = "kept", class = "a", style = { color: color }, "x" },
+            span { "y" }
+        }));
+
+        assert_eq!(
+            container.inner_html(),
+            "<p title=\"kept\" class=\"a\" style=\"color: red;\">x</p><span>y</span>"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_nodes_after_the_root_are_left_alone() {
+        let container = create_element(Namespace::Html, "div");
+        container.set_inner_html("<div id=\"app\"><p>x</p></div><script src=\"app.js\"></script>");
+
+        vdom::hydrate(&container, rsx!(div { id = "app", p { "x" } }));
+
+        assert_eq!(container.inner_html(), "<div id=\"app\"><p>x</p></div><script src=\"app.js\"></script>");
+    }
+}
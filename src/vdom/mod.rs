@@ -0,0 +1,305 @@
+mod boundary;
+mod children;
+mod component;
+mod context;
+mod hooks;
+mod hydrate;
+mod patch;
+mod preserve;
+mod ssr;
+
+pub use boundary::{error_boundary, RenderError, Retry};
+pub use children::Children;
+pub use component::{component, Component, VComponent};
+pub use context::{provide_context, use_context, with_root_context};
+pub use hooks::{use_memo, use_reducer, use_state, Reducer, State};
+pub use patch::{hydrate, mount, render, unmount};
+pub use ssr::render_to_string;
+
+use std::borrow::Cow;
+use std::rc::Rc;
+use web_sys::{Element, Node, Text};
+use crate::events::{EventListener, Handler};
+use crate::rsx::Namespace;
+use crate::signals::Effect;
+
+/// A node of the tree `rsx!` builds. Rendering it with `render` diffs it
+/// against the tree rendered last time and only touches the DOM where the
+/// two differ.
+pub enum VNode {
+    Element(Box<VElement>),
+    Template(Box<VTemplate>),
+    Text(VText),
+    Component(Box<VComponent>),
+    Portal(Box<VPortal>),
+    /// Siblings without a wrapper. Flattened into whatever they are added
+    /// to, so fragments never appear inside a mounted tree.
+    Fragment(Vec<VNode>),
+}
+
+impl VNode {
+    pub fn text(text: impl Into<Cow<'static, str>>) -> Self {
+        VNode::Text(VText::new(text))
+    }
+
+    pub fn template(skeleton: &'static StaticElement, key: Option<String>, holes: Vec<Hole>) -> Self {
+        VNode::Template(Box::new(VTemplate { skeleton, key, holes, root: None }))
+    }
+
+    /// Identifies the node among its siblings across renders.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            VNode::Element(element) => element.key.as_deref(),
+            VNode::Template(template) => template.key.as_deref(),
+            VNode::Text(_) | VNode::Component(_) | VNode::Portal(_) | VNode::Fragment(_) => None,
+        }
+    }
+
+    /// Adds `self` to `children`, splicing fragments in.
+    pub fn flatten_into(self, children: &mut Vec<VNode>) {
+        match self {
+            VNode::Fragment(nodes) => {
+                for node in nodes {
+                    node.flatten_into(children);
+                }
+            }
+            node => children.push(node),
+        }
+    }
+}
+
+impl Default for VNode {
+    fn default() -> Self {
+        VNode::Fragment(Vec::new())
+    }
+}
+
+impl From<VElement> for VNode {
+    fn from(element: VElement) -> Self {
+        VNode::Element(Box::new(element))
+    }
+}
+
+impl From<VText> for VNode {
+    fn from(text: VText) -> Self {
+        VNode::Text(text)
+    }
+}
+
+impl From<Vec<VNode>> for VNode {
+    fn from(nodes: Vec<VNode>) -> Self {
+        VNode::Fragment(nodes)
+    }
+}
+
+impl From<Option<VNode>> for VNode {
+    fn from(node: Option<VNode>) -> Self {
+        node.unwrap_or_default()
+    }
+}
+
+pub struct VElement {
+    pub ns: Namespace,
+    pub tag: &'static str,
+    pub key: Option<String>,
+    pub items: ElementItems,
+    pub children: Vec<VNode>,
+    node: Option<Element>,
+}
+
+impl VElement {
+    pub fn new(ns: Namespace, tag: &'static str) -> Self {
+        Self { ns, tag, key: None, items: ElementItems::default(), children: Vec::new(), node: None }
+    }
+}
+
+/// Everything `rsx!` sets on an element besides its children.
+#[derive(Default)]
+pub struct ElementItems {
+    /// `None` leaves the attribute off, as `false` and `None` values do
+    pub attributes: Vec<(&'static str, Option<String>)>,
+    pub properties: Vec<(&'static str, PropValue)>,
+    pub classes: Vec<Cow<'static, str>>,
+    /// `None` leaves the property unset
+    pub styles: Vec<(&'static str, Option<String>)>,
+    pub listeners: Vec<(&'static str, Handler)>,
+    pub node_refs: Vec<RefBinder>,
+    pub bindings: Vec<Binding>,
+    // Attached listeners, which take over `listeners` once mounted
+    mounted: Vec<EventListener>,
+}
+
+/// A `NodeRef`, bound to the element once it is mounted and cleared when
+/// the element is removed.
+pub type RefBinder = Box<dyn BindRef>;
+
+pub trait BindRef {
+    fn bind(&self, elem: &Element);
+
+    /// Clears the ref if it still points at `elem`.
+    fn unbind(&self, elem: &Element);
+}
+
+/// Value of a DOM property set with `.name = value`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl From<bool> for PropValue {
+    fn from(value: bool) -> Self {
+        PropValue::Bool(value)
+    }
+}
+
+impl From<&str> for PropValue {
+    fn from(value: &str) -> Self {
+        PropValue::String(value.to_string())
+    }
+}
+
+impl From<&String> for PropValue {
+    fn from(value: &String) -> Self {
+        PropValue::String(value.clone())
+    }
+}
+
+impl From<String> for PropValue {
+    fn from(value: String) -> Self {
+        PropValue::String(value)
+    }
+}
+
+macro_rules! impl_prop_value_number {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for PropValue {
+            fn from(value: $ty) -> Self {
+                PropValue::Number(value as f64)
+            }
+        })*
+    };
+}
+
+impl_prop_value_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// A subtree cloned from a skeleton the macro laid out at compile time.
+/// Only its holes change between renders.
+pub struct VTemplate {
+    skeleton: &'static StaticElement,
+    key: Option<String>,
+    holes: Vec<Hole>,
+    root: Option<Element>,
+}
+
+/// A dynamic part of a template, found by the path of child indices that
+/// leads to it from the template's root.
+pub struct Hole {
+    path: &'static [u32],
+    content: HoleContent,
+    node: Option<Node>,
+}
+
+enum HoleContent {
+    Element(ElementItems),
+    Text(VText),
+    /// Rendered in front of a comment marker
+    Children(Vec<VNode>),
+}
+
+impl Hole {
+    pub fn element(path: &'static [u32], items: ElementItems) -> Self {
+        Self { path, content: HoleContent::Element(items), node: None }
+    }
+
+    pub fn text(path: &'static [u32], text: VText) -> Self {
+        Self { path, content: HoleContent::Text(text), node: None }
+    }
+
+    pub fn children(path: &'static [u32], children: Vec<VNode>) -> Self {
+        Self { path, content: HoleContent::Children(children), node: None }
+    }
+}
+
+/// Renders `children` at the end of the element `target` selects, such as
+/// `"body"` or `"#modals"`, instead of in place. Tooltips, modals and toasts
+/// escape the `overflow` and stacking context of their parents that way.
+/// The target is looked up once the patch is in the document; if nothing
+/// matches, the portal warns and renders nothing until a later patch finds
+/// it.
+///
+/// The portal still belongs to the tree it is placed in: its children are
+/// patched with it, their handlers are the component's closures, and they
+/// are removed (with `before_unmount` called) when it is.
+pub fn portal(target: impl Into<Cow<'static, str>>, children: impl Into<VNode>) -> VNode {
+    let mut flattened = Vec::new();
+    children.into().flatten_into(&mut flattened);
+    VNode::Portal(Box::new(VPortal { target: target.into(), children: flattened, placeholder: None, end: None }))
+}
+
+pub struct VPortal {
+    target: Cow<'static, str>,
+    children: Vec<VNode>,
+    // Comment standing in for the portal where it is placed
+    placeholder: Option<Node>,
+    // Comment in the target that the children are rendered in front of
+    end: Option<Node>,
+}
+
+pub struct VText {
+    text: Cow<'static, str>,
+    binding: Option<Binding>,
+    node: Option<Text>,
+}
+
+impl VText {
+    pub fn new(text: impl Into<Cow<'static, str>>) -> Self {
+        Self { text: text.into(), binding: None, node: None }
+    }
+
+    /// Text that follows the signals `text` reads, like `@count`.
+    pub fn bound(source: usize, text: impl Fn() -> String + 'static) -> Self {
+        let text = Rc::new(text);
+        let current = text();
+        Self { text: current.into(), binding: Some(Binding::new(source, BindingKind::Text(text))), node: None }
+    }
+}
+
+/// A signal-backed part of the DOM. Once mounted, an effect writes the
+/// value whenever a signal it reads changes, without a render.
+pub struct Binding {
+    /// Id of the signal or memo bound, so a render that binds the same one
+    /// keeps the effect
+    source: usize,
+    kind: BindingKind,
+    effect: Option<Effect>,
+}
+
+pub enum BindingKind {
+    Text(Rc<dyn Fn() -> String>),
+    Attribute(&'static str, Rc<dyn Fn() -> Option<String>>),
+    Style(&'static str, Rc<dyn Fn() -> Option<String>>),
+    Class(Rc<dyn Fn() -> Vec<Cow<'static, str>>>),
+}
+
+impl Binding {
+    pub fn new(source: usize, kind: BindingKind) -> Self {
+        Self { source, kind, effect: None }
+    }
+}
+
+/// The static part of an `rsx!` subtree.
+pub struct StaticElement {
+    pub ns: Namespace,
+    pub tag: &'static str,
+    pub attributes: &'static [(&'static str, &'static str)],
+    pub children: &'static [StaticNode],
+}
+
+pub enum StaticNode {
+    Element(StaticElement),
+    Text(&'static str),
+    /// Where a run of dynamic children goes
+    Marker,
+}
@@ -0,0 +1,910 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::mem;
+use std::rc::{Rc, Weak};
+use wasm_bindgen::JsCast;
+use web_sys::{DocumentFragment, Element, HtmlTemplateElement, Node};
+
+use super::boundary::{catch, show_fallbacks, Catch};
+use super::component::ComponentState;
+use super::context::{with_context, Context};
+use super::hydrate::adopt_root;
+use super::preserve;
+use super::{Binding, BindingKind, ElementItems, HoleContent, PropValue, StaticElement, StaticNode, VNode, VPortal, VText};
+use crate::events::EventListener;
+use crate::signals::Effect;
+use crate::rsx::{create_element, style, Namespace};
+use crate::utils::get_document;
+
+thread_local! {
+    // Mounted tree of every container passed to `render`
+    static ROOTS: RefCell<Vec<(Element, Vec<VNode>)>> = const { RefCell::new(Vec::new()) };
+    // Patches requested while another one runs, e.g. by a blur handler that
+    // fires when the focused element is removed
+    static PENDING: RefCell<Option<Vec<Patch>>> = const { RefCell::new(None) };
+    // DOM of each skeleton, keyed by its address
+    static TEMPLATES: RefCell<HashMap<usize, Element>> = RefCell::new(HashMap::new());
+    // Hooks to call once the patch in progress is in the document
+    static LIFECYCLE: RefCell<Vec<(Rc<ComponentState>, Lifecycle)>> = const { RefCell::new(Vec::new()) };
+    // Content of the portals not in their target yet, in document order
+    static PORTALS: RefCell<Vec<Detached>> = const { RefCell::new(Vec::new()) };
+}
+
+// The content of a portal, kept out of the page until its target exists
+struct Detached {
+    target: Cow<'static, str>,
+    content: DocumentFragment,
+    warned: bool,
+}
+
+enum Lifecycle {
+    Mounted,
+    Updated,
+}
+
+enum Patch {
+    Root(Element, VNode),
+    Hydrate(Element, VNode),
+    // A component re-rendering after its own state changed
+    Component(Rc<ComponentState>),
+}
+
+/// Renders `node` into `container`, patching what the previous call left
+/// there. Listeners of nodes that go away are removed with them.
+pub fn render(container: &Element, node: impl Into<VNode>) {
+    run(Patch::Root(container.clone(), node.into()));
+}
+
+/// Like `render`, but takes over the DOM already in `container`, such as
+/// markup from `render_to_string`, instead of building it: listeners,
+/// refs and bindings are attached to the existing nodes. Whatever doesn't
+/// match `node` is replaced or removed with a warning in the console;
+/// nodes in `container` after the ones `node` claims are left alone.
+pub fn hydrate(container: &Element, node: impl Into<VNode>) {
+    run(Patch::Hydrate(container.clone(), node.into()));
+}
+
+/// Re-renders the component on the next frame, unless a render of its
+/// parent gets to it first.
+pub(super) fn schedule_update(component: &Weak<ComponentState>) {
+    let Some(state) = component.upgrade() else {
+        return;
+    };
+    if state.dirty.replace(true) {
+        return;
+    }
+    let component = component.clone();
+    crate::scheduler::schedule(move || {
+        if let Some(state) = component.upgrade() {
+            run(Patch::Component(state));
+        }
+    });
+}
+
+/// Re-renders the component as part of the patch in progress, after what it
+/// is patching now; on its own if no patch is running.
+pub(super) fn update_now(component: &Weak<ComponentState>) {
+    if let Some(state) = component.upgrade() {
+        state.dirty.set(true);
+        run(Patch::Component(state));
+    }
+}
+
+/// Whether a patch is running, so what it queues gets handled before it ends.
+pub(super) fn is_patching() -> bool {
+    PENDING.with(|pending| pending.borrow().is_some())
+}
+
+fn run(patch: Patch) {
+    let started = PENDING.with(|pending| {
+        let mut pending = pending.borrow_mut();
+        match pending.as_mut() {
+            Some(queue) => {
+                queue.push(patch);
+                None
+            }
+            None => {
+                *pending = Some(Vec::new());
+                Some(patch)
+            }
+        }
+    });
+
+    let mut next = started;
+    while let Some(patch) = next {
+        let focus = preserve::save_focus();
+        match patch {
+            Patch::Root(container, node) => patch_root(&container, node),
+            Patch::Hydrate(container, node) => hydrate_root(&container, node),
+            Patch::Component(state) => update_component(&state),
+        }
+        attach_portals();
+        show_fallbacks();
+        preserve::restore_drafts();
+        if let Some(focus) = focus {
+            preserve::restore_focus(focus);
+        }
+
+        // Children were queued before their parents, so they run first
+        for (state, lifecycle) in LIFECYCLE.with(|queue| queue.take()) {
+            let mut instance = state.instance.borrow_mut();
+            match lifecycle {
+                Lifecycle::Mounted => instance.mounted(),
+                Lifecycle::Updated => instance.updated(),
+            }
+        }
+
+        next = PENDING.with(|pending| {
+            let mut pending = pending.borrow_mut();
+            let queue = pending.as_mut().unwrap();
+            if queue.is_empty() {
+                *pending = None;
+                None
+            } else {
+                Some(queue.remove(0))
+            }
+        });
+    }
+}
+
+/// Removes what `render` put in `container` and drops its listeners.
+pub fn unmount(container: &Element) {
+    for node in take_root(container) {
+        remove(node);
+    }
+    preserve::discard_drafts();
+}
+
+/// Renders `node` into a new detached `<div>` and returns the div.
+pub fn mount(node: impl Into<VNode>) -> Element {
+    let container = create_element(Namespace::Html, "div");
+    render(&container, node);
+    container
+}
+
+fn take_root(container: &Element) -> Vec<VNode> {
+    ROOTS.with(|roots| {
+        let mut roots = roots.borrow_mut();
+        let index = roots.iter().position(|(root, _)| root.is_same_node(Some(container)));
+        index.map(|index| roots.swap_remove(index).1).unwrap_or_default()
+    })
+}
+
+fn patch_root(container: &Element, node: VNode) {
+    let old = take_root(container);
+    let mut new = Vec::new();
+    node.flatten_into(&mut new);
+
+    let mounted = diff_children(container, old, new, None);
+    ROOTS.with(|roots| roots.borrow_mut().push((container.clone(), mounted)));
+}
+
+fn hydrate_root(container: &Element, node: VNode) {
+    drop(take_root(container));
+    let mut new = Vec::new();
+    node.flatten_into(&mut new);
+
+    let mounted = adopt_root(container, new);
+    ROOTS.with(|roots| roots.borrow_mut().push((container.clone(), mounted)));
+}
+
+/// Whether `elem` is a container passed to `render` or `hydrate`.
+pub(super) fn is_root(elem: &Element) -> bool {
+    ROOTS.with(|roots| roots.borrow().iter().any(|(root, _)| root.is_same_node(Some(elem))))
+}
+
+pub(super) fn queue_mounted(state: &Rc<ComponentState>) {
+    LIFECYCLE.with(|queue| queue.borrow_mut().push((state.clone(), Lifecycle::Mounted)));
+}
+
+fn update_component(state: &Rc<ComponentState>) {
+    // Cleared when a parent render re-rendered it in the meantime
+    if !state.dirty.replace(false) {
+        return;
+    }
+    let Some(parent) = state.marker.parent_node() else {
+        return;
+    };
+    let (output, context) = state.render();
+    let old = state.output.take();
+    let (output, _) = with_context(context, || diff_children(&parent, old, output, Some(&state.marker)));
+    *state.output.borrow_mut() = output;
+    LIFECYCLE.with(|queue| queue.borrow_mut().push((state.clone(), Lifecycle::Updated)));
+}
+
+/// Reconciles the children of `parent` that sit in front of `anchor` (or at
+/// the end). Keyed nodes are matched by key wherever they moved; the rest
+/// are matched in order.
+fn diff_children(parent: &Node, old: Vec<VNode>, new: Vec<VNode>, anchor: Option<&Node>) -> Vec<VNode> {
+    let mut keyed = HashMap::new();
+    let mut unkeyed = Vec::new();
+    for (index, node) in old.iter().enumerate() {
+        match node.key() {
+            Some(key) => {
+                keyed.insert(key.to_string(), index);
+            }
+            None => unkeyed.push(index),
+        }
+    }
+    let mut unkeyed = unkeyed.into_iter();
+    let mut old: Vec<_> = old.into_iter().map(Some).collect();
+
+    let mut mounted = Vec::with_capacity(new.len());
+    // Whether every node kept its DOM node and relative order
+    let mut in_place = true;
+    let mut last_index = None;
+    for node in new {
+        let index = match node.key() {
+            Some(key) => keyed.remove(key),
+            None => unkeyed.next(),
+        };
+        let matched = index.and_then(|index| old[index].take().map(|old| (index, old)));
+        let node = match matched {
+            Some((index, old)) if is_same_kind(&old, &node) => {
+                in_place &= last_index.is_none_or(|last| index > last);
+                last_index = Some(index);
+                patch(old, node)
+            }
+            Some((_, old)) => {
+                remove(old);
+                in_place = false;
+                create(node)
+            }
+            None => {
+                in_place = false;
+                create(node)
+            }
+        };
+        mounted.push(node);
+    }
+
+    for node in old.into_iter().flatten() {
+        remove(node);
+    }
+
+    if !in_place {
+        let mut next = anchor.cloned();
+        let mut nodes = Vec::new();
+        for node in &mounted {
+            dom_nodes(node, &mut nodes);
+        }
+        for dom in nodes.into_iter().rev() {
+            let placed = dom.parent_node().is_some_and(|current| current.is_same_node(Some(parent)))
+                && match (&dom.next_sibling(), &next) {
+                    (Some(sibling), Some(next)) => sibling.is_same_node(Some(next)),
+                    (None, None) => true,
+                    _ => false,
+                };
+            if !placed {
+                catch(parent.insert_before(&dom, next.as_ref()));
+            }
+            next = Some(dom);
+        }
+    }
+
+    mounted
+}
+
+fn is_same_kind(old: &VNode, new: &VNode) -> bool {
+    match (old, new) {
+        (VNode::Element(old), VNode::Element(new)) => old.tag == new.tag && old.ns == new.ns,
+        (VNode::Template(old), VNode::Template(new)) => std::ptr::eq(old.skeleton, new.skeleton),
+        (VNode::Text(_), VNode::Text(_)) => true,
+        (VNode::Component(old), VNode::Component(new)) => old.type_id == new.type_id,
+        (VNode::Portal(old), VNode::Portal(new)) => old.target == new.target,
+        _ => false,
+    }
+}
+
+/// Collects the top-level DOM nodes of `node`: one, except for components,
+/// which have their output followed by their marker.
+fn dom_nodes(node: &VNode, nodes: &mut Vec<Node>) {
+    match node {
+        VNode::Element(element) => nodes.push(element.node.clone().unwrap().into()),
+        VNode::Template(template) => nodes.push(template.root.clone().unwrap().into()),
+        VNode::Text(text) => nodes.push(text.node.clone().unwrap().into()),
+        VNode::Component(component) => {
+            let state = component.state.as_ref().unwrap();
+            for node in state.output.borrow().iter() {
+                dom_nodes(node, nodes);
+            }
+            nodes.push(state.marker.clone());
+        }
+        VNode::Portal(portal) => nodes.push(portal.placeholder.clone().unwrap()),
+        VNode::Fragment(_) => unreachable!("fragments are flattened before mounting"),
+    }
+}
+
+/// Creates the DOM for `node`, leaving it detached.
+fn create(node: VNode) -> VNode {
+    match node {
+        VNode::Element(mut element) => {
+            let elem = create_element(element.ns, element.tag);
+            patch_items(&elem, ElementItems::default(), &mut element.items);
+            let mut nodes = Vec::new();
+            for child in mem::take(&mut element.children) {
+                let child = create(child);
+                dom_nodes(&child, &mut nodes);
+                element.children.push(child);
+            }
+            for node in nodes {
+                catch(elem.append_child(&node));
+            }
+            element.node = Some(elem);
+            VNode::Element(element)
+        }
+        VNode::Template(mut template) => {
+            let root = instantiate(template.skeleton);
+            // Find every hole before filling any: dynamic children shift the
+            // indices of the nodes after them
+            for hole in &mut template.holes {
+                hole.node = Some(node_at(&root, hole.path));
+            }
+            for hole in &mut template.holes {
+                let node = hole.node.as_ref().unwrap();
+                match &mut hole.content {
+                    HoleContent::Element(items) => {
+                        patch_items(node.unchecked_ref(), ElementItems::default(), items);
+                    }
+                    HoleContent::Text(text) => patch_text(node, None, text),
+                    HoleContent::Children(children) => {
+                        *children = diff_children(&node.parent_node().unwrap(), Vec::new(), mem::take(children), Some(node));
+                    }
+                }
+            }
+            template.root = Some(root);
+            VNode::Template(template)
+        }
+        VNode::Text(mut text) => {
+            let node = get_document().create_text_node("");
+            patch_text(&node, None, &mut text);
+            text.node = Some(node);
+            VNode::Text(text)
+        }
+        VNode::Component(mut component) => {
+            let instance = (component.create)(component.props.take().unwrap());
+            let state = Rc::new(ComponentState {
+                instance: RefCell::new(instance),
+                output: RefCell::default(),
+                marker: get_document().create_comment("").into(),
+                hooks: Rc::default(),
+                context: RefCell::new(Context::current()),
+                dirty: Cell::new(false),
+            });
+            let (output, context) = state.render();
+            let (output, _) = with_context(context, || output.into_iter().map(create).collect());
+            *state.output.borrow_mut() = output;
+
+            queue_mounted(&state);
+            component.state = Some(state);
+            VNode::Component(component)
+        }
+        VNode::Portal(mut portal) => {
+            portal.placeholder = Some(get_document().create_comment("").into());
+            open_portal(&mut portal);
+            VNode::Portal(portal)
+        }
+        VNode::Fragment(_) => unreachable!("fragments are flattened before mounting"),
+    }
+}
+
+/// Renders the children of `portal` into a fragment, which
+/// `attach_portals` moves into the target once the patch is done.
+pub(super) fn open_portal(portal: &mut VPortal) {
+    let fragment = get_document().create_document_fragment();
+    let end: Node = get_document().create_comment("").into();
+    catch(fragment.append_child(&end));
+    // Queued before its children, so portals they open come after it
+    let detached = Detached { target: portal.target.clone(), content: fragment.clone(), warned: false };
+    PORTALS.with(|queue| queue.borrow_mut().push(detached));
+    portal.children = diff_children(&fragment, Vec::new(), mem::take(&mut portal.children), Some(&end));
+    portal.end = Some(end);
+}
+
+// Looks the targets up once the patch has put the new nodes in the
+// document, so a portal can target an element rendered next to it. A
+// missing target leaves the content in its fragment, out of the page, and
+// is looked up again after every later patch.
+fn attach_portals() {
+    let mut waiting = PORTALS.with(|queue| queue.take());
+    waiting.retain_mut(|detached| {
+        // Emptied by `teardown` when the portal went before its target came
+        if !detached.content.has_child_nodes() {
+            return false;
+        }
+        match get_document().query_selector(&detached.target).ok().flatten() {
+            Some(elem) => {
+                catch(elem.append_child(&detached.content));
+                false
+            }
+            None => {
+                if !mem::replace(&mut detached.warned, true) {
+                    let message = format!("portal target `{}` not found, so nothing is rendered yet", detached.target);
+                    web_sys::console::warn_1(&message.into());
+                }
+                true
+            }
+        }
+    });
+    PORTALS.with(|queue| *queue.borrow_mut() = waiting);
+}
+
+/// Moves the DOM of `old` over to `new` and updates what differs.
+fn patch(old: VNode, new: VNode) -> VNode {
+    match (old, new) {
+        (VNode::Element(old), VNode::Element(mut new)) => {
+            let old = *old;
+            let elem = old.node.unwrap();
+            patch_items(&elem, old.items, &mut new.items);
+            new.children = diff_children(&elem, old.children, mem::take(&mut new.children), None);
+            new.node = Some(elem);
+            VNode::Element(new)
+        }
+        (VNode::Template(old), VNode::Template(mut new)) => {
+            // Same skeleton, so the holes line up one to one
+            for (old, hole) in old.holes.into_iter().zip(&mut new.holes) {
+                let node = old.node.unwrap();
+                match (old.content, &mut hole.content) {
+                    (HoleContent::Element(old), HoleContent::Element(items)) => {
+                        patch_items(node.unchecked_ref(), old, items);
+                    }
+                    (HoleContent::Text(old), HoleContent::Text(text)) => patch_text(&node, Some(old), text),
+                    (HoleContent::Children(old), HoleContent::Children(children)) => {
+                        let parent = node.parent_node().unwrap();
+                        *children = diff_children(&parent, old, mem::take(children), Some(&node));
+                    }
+                    _ => unreachable!("holes of one skeleton line up"),
+                }
+                hole.node = Some(node);
+            }
+            new.root = old.root;
+            VNode::Template(new)
+        }
+        (VNode::Text(mut old), VNode::Text(mut new)) => {
+            let node = old.node.take().unwrap();
+            patch_text(&node, Some(old), &mut new);
+            new.node = Some(node);
+            VNode::Text(new)
+        }
+        (VNode::Component(old), VNode::Component(mut new)) => {
+            let state = old.state.unwrap();
+            state.dirty.set(false);
+            state.instance.borrow_mut().set_props(new.props.take().unwrap());
+            *state.context.borrow_mut() = Context::current();
+            let (output, context) = state.render();
+            let old_output = state.output.take();
+            let parent = state.marker.parent_node().unwrap();
+            let (output, _) =
+                with_context(context, || diff_children(&parent, old_output, output, Some(&state.marker)));
+            *state.output.borrow_mut() = output;
+
+            LIFECYCLE.with(|queue| queue.borrow_mut().push((state.clone(), Lifecycle::Updated)));
+            new.state = Some(state);
+            VNode::Component(new)
+        }
+        (VNode::Portal(old), VNode::Portal(mut new)) => {
+            let end = old.end.unwrap();
+            let target = end.parent_node().unwrap();
+            new.children = diff_children(&target, old.children, mem::take(&mut new.children), Some(&end));
+            new.placeholder = old.placeholder;
+            new.end = Some(end);
+            VNode::Portal(new)
+        }
+        _ => unreachable!("only nodes of the same kind are patched"),
+    }
+}
+
+fn remove(node: VNode) {
+    teardown(&node);
+
+    let mut nodes = Vec::new();
+    dom_nodes(&node, &mut nodes);
+    for dom in nodes {
+        preserve::save_drafts(&dom);
+        if let Some(parent) = dom.parent_node() {
+            catch(parent.remove_child(&dom));
+        }
+    }
+    // Dropping the node drops the listeners of its whole subtree
+}
+
+/// Calls `before_unmount` on the components in `node`, outermost first,
+/// clears its refs and takes the content of its portals out of their targets.
+fn teardown(node: &VNode) {
+    match node {
+        VNode::Element(element) => {
+            unbind_refs(element.node.as_ref().unwrap(), &element.items);
+            element.children.iter().for_each(teardown);
+        }
+        VNode::Template(template) => {
+            for hole in &template.holes {
+                match &hole.content {
+                    HoleContent::Element(items) => unbind_refs(hole.node.as_ref().unwrap().unchecked_ref(), items),
+                    HoleContent::Children(children) => children.iter().for_each(teardown),
+                    HoleContent::Text(_) => {}
+                }
+            }
+        }
+        VNode::Component(component) => {
+            let state = component.state.as_ref().unwrap();
+            state.instance.borrow_mut().before_unmount();
+            state.output.borrow().iter().for_each(teardown);
+        }
+        VNode::Portal(portal) => {
+            portal.children.iter().for_each(teardown);
+            let mut nodes = Vec::new();
+            for child in &portal.children {
+                dom_nodes(child, &mut nodes);
+            }
+            nodes.extend(portal.end.clone());
+            for dom in nodes {
+                if let Some(parent) = dom.parent_node() {
+                    catch(parent.remove_child(&dom));
+                }
+            }
+        }
+        VNode::Text(_) | VNode::Fragment(_) => {}
+    }
+}
+
+fn unbind_refs(elem: &Element, items: &ElementItems) {
+    for node_ref in &items.node_refs {
+        node_ref.unbind(elem);
+    }
+}
+
+/// Applies `new` to `elem`, which currently reflects `old`.
+pub(super) fn patch_items(elem: &Element, old: ElementItems, new: &mut ElementItems) {
+    for (name, value) in &new.attributes {
+        let previous = old.attributes.iter().find(|(old_name, _)| old_name == name);
+        if previous.and_then(|(_, value)| value.as_ref()) != value.as_ref() {
+            match value {
+                Some(value) => catch(elem.set_attribute(name, value)),
+                None => catch(elem.remove_attribute(name)),
+            };
+        }
+    }
+    for (name, value) in &old.attributes {
+        if value.is_some() && !new.attributes.iter().any(|(new_name, _)| new_name == name) {
+            catch(elem.remove_attribute(name));
+        }
+    }
+
+    for (name, value) in &new.properties {
+        let previous = old.properties.iter().find(|(old_name, _)| old_name == name);
+        if previous.map(|(_, value)| value) != Some(value) {
+            let value = match value {
+                PropValue::Bool(value) => (*value).into(),
+                PropValue::Number(value) => (*value).into(),
+                PropValue::String(value) => value.into(),
+            };
+            catch(js_sys::Reflect::set(elem, &(*name).into(), &value));
+        }
+    }
+
+    if old.classes != new.classes {
+        let class_list = elem.class_list();
+        for class in &old.classes {
+            if !new.classes.contains(class) {
+                catch(class_list.remove_1(class));
+            }
+        }
+        for class in &new.classes {
+            if !old.classes.contains(class) {
+                catch(class_list.add_1(class));
+            }
+        }
+    }
+
+    if old.styles != new.styles {
+        let declarations = style(elem);
+        for (property, value) in &new.styles {
+            match value {
+                Some(value) => {
+                    catch(declarations.set_property(property, value));
+                }
+                None => {
+                    catch(declarations.remove_property(property));
+                }
+            }
+        }
+        for (property, _) in &old.styles {
+            if !new.styles.iter().any(|(new_property, _)| new_property == property) {
+                catch(declarations.remove_property(property));
+            }
+        }
+    }
+
+    // Handlers are new closures every render; listeners for the same events
+    // are kept and just handed the new handler
+    let mut attached = old.mounted.into_iter();
+    for (event, handler) in new.listeners.drain(..) {
+        let listener = match attached.next() {
+            Some(listener) if listener.event() == event => {
+                listener.set_handler(handler);
+                listener
+            }
+            _ => EventListener::new(elem, event, handler),
+        };
+        new.mounted.push(listener);
+    }
+
+    for node_ref in &old.node_refs {
+        node_ref.unbind(elem);
+    }
+    for node_ref in &new.node_refs {
+        node_ref.bind(elem);
+    }
+
+    let mut bound = old.bindings.into_iter();
+    for binding in &mut new.bindings {
+        patch_binding(elem, bound.next(), binding);
+    }
+}
+
+/// Updates the text node `node`, which currently shows `old` (or nothing).
+pub(super) fn patch_text(node: &Node, old: Option<VText>, new: &mut VText) {
+    let (text, binding) = match old {
+        Some(old) => (old.text, old.binding),
+        None => (Cow::Borrowed(""), None),
+    };
+    match &mut new.binding {
+        Some(new_binding) => patch_binding(node, binding, new_binding),
+        None => {
+            if binding.is_some() || text != new.text {
+                node.set_text_content(Some(&new.text));
+            }
+        }
+    }
+}
+
+/// Starts the effect that keeps `node` in sync with `new`, or takes over
+/// the one of `old` when it binds the same signal to the same target.
+fn patch_binding(node: &Node, old: Option<Binding>, new: &mut Binding) {
+    if let Some(old) = old {
+        if old.source == new.source && same_target(&old.kind, &new.kind) {
+            new.effect = old.effect;
+            return;
+        }
+    }
+
+    // The effects rerun outside any render, so they keep the boundary around
+    // the element now
+    let boundary = Catch::current();
+    let effect = match &new.kind {
+        BindingKind::Text(text) => {
+            let (node, text) = (node.clone(), text.clone());
+            Effect::new(move || node.set_text_content(Some(&text())))
+        }
+        BindingKind::Attribute(name, value) => {
+            let (elem, name, value) = (node.clone().unchecked_into::<Element>(), *name, value.clone());
+            Effect::new(move || {
+                match value() {
+                    Some(value) => boundary.check(elem.set_attribute(name, &value)),
+                    None => boundary.check(elem.remove_attribute(name)),
+                };
+            })
+        }
+        BindingKind::Style(property, value) => {
+            let (declarations, property, value) = (style(node.unchecked_ref()), *property, value.clone());
+            Effect::new(move || match value() {
+                Some(value) => {
+                    boundary.check(declarations.set_property(property, &value));
+                }
+                None => {
+                    boundary.check(declarations.remove_property(property));
+                }
+            })
+        }
+        BindingKind::Class(classes) => {
+            let (class_list, classes) = (node.unchecked_ref::<Element>().class_list(), classes.clone());
+            let mut previous = Vec::new();
+            Effect::new(move || {
+                let current = classes();
+                for class in &previous {
+                    if !current.contains(class) {
+                        boundary.check(class_list.remove_1(class));
+                    }
+                }
+                for class in &current {
+                    if !previous.contains(class) {
+                        boundary.check(class_list.add_1(class));
+                    }
+                }
+                previous = current;
+            })
+        }
+    };
+    new.effect = Some(effect);
+}
+
+fn same_target(old: &BindingKind, new: &BindingKind) -> bool {
+    match (old, new) {
+        (BindingKind::Text(_), BindingKind::Text(_)) | (BindingKind::Class(_), BindingKind::Class(_)) => true,
+        (BindingKind::Attribute(old, _), BindingKind::Attribute(new, _)) => old == new,
+        (BindingKind::Style(old, _), BindingKind::Style(new, _)) => old == new,
+        _ => false,
+    }
+}
+
+/// Clones the DOM of `skeleton`, building it on first use.
+fn instantiate(skeleton: &'static StaticElement) -> Element {
+    TEMPLATES.with(|templates| {
+        let mut templates = templates.borrow_mut();
+        let root = templates
+            .entry(skeleton as *const StaticElement as usize)
+            .or_insert_with(|| {
+                // Kept in a `<template>`'s inert content, where images don't
+                // load and custom elements aren't upgraded until cloned out
+                let template = create_element(Namespace::Html, "template").unchecked_into::<HtmlTemplateElement>();
+                let root = build_static(skeleton);
+                catch(template.content().append_child(&root));
+                root
+            });
+        match catch(root.clone_node_with_deep(true)) {
+            Some(clone) => clone.unchecked_into(),
+            None => build_static(skeleton),
+        }
+    })
+}
+
+fn build_static(element: &StaticElement) -> Element {
+    let document = get_document();
+    let elem = create_element(element.ns, element.tag);
+    for (name, value) in element.attributes {
+        catch(elem.set_attribute(name, value));
+    }
+    for child in element.children {
+        let child: Node = match child {
+            StaticNode::Element(child) => build_static(child).into(),
+            StaticNode::Text(text) => document.create_text_node(text).into(),
+            StaticNode::Marker => document.create_comment("").into(),
+        };
+        catch(elem.append_child(&child));
+    }
+    elem
+}
+
+/// Follows child indices down from `root`.
+fn node_at(root: &Element, path: &[u32]) -> Node {
+    path.iter().fold(root.clone().into(), |node: Node, &index| {
+        node.child_nodes().item(index).unwrap()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsx;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn list(ids: &[usize]) -> VNode {
+        rsx!(ul {
+            for id in ids {
+                li { key = id, class = "item", span { @id } }
+            }
+        })
+    }
+
+    fn items(container: &Element) -> Vec<Node> {
+        let items = container.query_selector_all("li").unwrap();
+        (0..items.length()).map(|i| items.get(i).unwrap()).collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_keyed_children_keep_their_nodes() {
+        let container = mount(list(&[1, 2, 3]));
+        let before = items(&container);
+
+        render(&container, list(&[3, 1, 4]));
+        let after = items(&container);
+
+        assert_eq!(container.text_content().unwrap(), "314");
+        assert!(after[0].is_same_node(Some(&before[2])));
+        assert!(after[1].is_same_node(Some(&before[0])));
+        assert!(!before[1].is_connected());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_unchanged_nodes_are_not_touched() {
+        let render_count = |count: i32| rsx!(div { "Counter: ", span { id = "count", @count }, button { "+" } });
+        let container = mount(render_count(0));
+        let button = container.query_selector("button").unwrap().unwrap();
+        let count = container.query_selector("#count").unwrap().unwrap();
+
+        render(&container, render_count(1));
+
+        assert!(button.is_same_node(container.query_selector("button").unwrap().as_deref()));
+        assert!(count.is_same_node(container.query_selector("#count").unwrap().as_deref()));
+        assert_eq!(count.text_content().unwrap(), "1");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_items_are_patched_in_place() {
+        let render_item = |done: bool| rsx!(input { type = "checkbox", class = "todo", class:done = done, disabled = done });
+        let container = mount(render_item(false));
+        let input = container.first_element_child().unwrap();
+
+        render(&container, render_item(true));
+        assert_eq!(input.class_name(), "todo done");
+        assert!(input.has_attribute("disabled"));
+
+        render(&container, render_item(false));
+        assert_eq!(input.class_name(), "todo");
+        assert!(!input.has_attribute("disabled"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_portals_render_into_their_target() {
+        let target = create_element(Namespace::Html, "div");
+        target.set_id("portal-target");
+        get_document().body().unwrap().append_child(&target).unwrap();
+        let view = |open: bool, text: &'static str| rsx!(div {
+            class = "card",
+            if open {
+                crate::portal("#portal-target", rsx!(p { class = "modal", @text }))
+            }
+        });
+
+        let container = mount(view(true, "first"));
+        let modal = target.query_selector(".modal").unwrap().unwrap();
+        assert!(container.query_selector(".modal").unwrap().is_none());
+
+        render(&container, view(true, "second"));
+        assert!(modal.is_same_node(target.query_selector(".modal").unwrap().as_deref()));
+        assert_eq!(modal.text_content().unwrap(), "second");
+
+        render(&container, view(false, "second"));
+        assert_eq!(target.child_nodes().length(), 0);
+        target.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_portals_can_target_elements_rendered_with_them() {
+        let container = create_element(Namespace::Html, "div");
+        get_document().body().unwrap().append_child(&container).unwrap();
+
+        render(&container, rsx!(div {
+            crate::portal("#modal-root", rsx!(p { "modal" })),
+            div { id = "modal-root" }
+        }));
+
+        let target = container.query_selector("#modal-root").unwrap().unwrap();
+        assert_eq!(target.text_content().unwrap(), "modal");
+        unmount(&container);
+        container.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_portals_without_a_target_render_nothing() {
+        let view = |text: &'static str| rsx!(div { crate::portal("#missing", rsx!(p { class = "lost", @text })) });
+
+        let container = mount(view("first"));
+        render(&container, view("second"));
+
+        assert!(get_document().query_selector(".lost").unwrap().is_none());
+        assert_eq!(container.inner_html(), "<div><!----></div>");
+        unmount(&container);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_portals_wait_for_targets_rendered_later() {
+        let container = create_element(Namespace::Html, "div");
+        get_document().body().unwrap().append_child(&container).unwrap();
+        let view = |target: bool| rsx!(div {
+            crate::portal("#later-root", rsx!(p { "modal" })),
+            if target { div { id = "later-root" } }
+        });
+
+        render(&container, view(false));
+        render(&container, view(true));
+
+        let target = container.query_selector("#later-root").unwrap().unwrap();
+        assert_eq!(target.text_content().unwrap(), "modal");
+        unmount(&container);
+        container.remove();
+    }
+}
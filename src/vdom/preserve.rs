@@ -0,0 +1,207 @@
+use std::cell::RefCell;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, HtmlInputElement, HtmlTextAreaElement, Node};
+
+use crate::utils::get_document;
+
+thread_local! {
+    // Text typed into inputs the patch in progress removed
+    static DRAFTS: RefCell<Vec<Draft>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Draft {
+    id: String,
+    input: Element,
+    value: String,
+}
+
+/// The focused element and its selection, from before a patch.
+pub(super) struct Focus {
+    element: HtmlElement,
+    selection: Option<Selection>,
+}
+
+struct Selection {
+    start: u32,
+    end: u32,
+    direction: String,
+}
+
+pub(super) fn save_focus() -> Option<Focus> {
+    let document = get_document();
+    let element = document.active_element()?.dyn_into::<HtmlElement>().ok()?;
+    if document.body().is_some_and(|body| body.is_same_node(Some(&element))) {
+        return None;
+    }
+    let selection = selection(&element);
+    Some(Focus { element, selection })
+}
+
+/// Focuses the element focused before the patch again, or the one that
+/// replaced it under the same id, and puts the caret back. Moving a node
+/// or rebuilding it blurs it otherwise.
+pub(super) fn restore_focus(focus: Focus) {
+    let document = get_document();
+    let element = if focus.element.is_connected() {
+        focus.element
+    } else {
+        let id = focus.element.id();
+        match document.get_element_by_id(&id).filter(|_| !id.is_empty()) {
+            Some(replacement) => replacement.unchecked_into(),
+            None => return,
+        }
+    };
+    if document.active_element().is_some_and(|active| active.is_same_node(Some(&element))) {
+        return;
+    }
+
+    let _ = element.focus();
+    if let Some(selection) = focus.selection {
+        set_selection(&element, &selection);
+    }
+}
+
+/// Remembers what the user typed into `node` or the inputs in it, so an
+/// input with the same id created by the same patch can take it over.
+pub(super) fn save_drafts(node: &Node) {
+    let Some(elem) = node.dyn_ref::<Element>() else {
+        return;
+    };
+    let mut inputs = vec![elem.clone()];
+    let nested = elem.query_selector_all("input[id], textarea[id]").unwrap();
+    inputs.extend((0..nested.length()).filter_map(|i| nested.get(i)?.dyn_into().ok()));
+
+    DRAFTS.with(|drafts| {
+        let mut drafts = drafts.borrow_mut();
+        for input in inputs {
+            if let Some(value) = typed_value(&input) {
+                drafts.push(Draft { id: input.id(), input, value });
+            }
+        }
+    });
+}
+
+/// Hands the saved drafts to their replacements, unless the render set a
+/// value on those itself, and forgets them.
+pub(super) fn restore_drafts() {
+    let document = get_document();
+    for draft in DRAFTS.with(|drafts| drafts.take()) {
+        let Some(input) = document.get_element_by_id(&draft.id) else {
+            continue;
+        };
+        if input.is_same_node(Some(&draft.input)) {
+            continue;
+        }
+        if let Some(input) = input.dyn_ref::<HtmlInputElement>() {
+            if input.value() == input.default_value() {
+                input.set_value(&draft.value);
+            }
+        } else if let Some(textarea) = input.dyn_ref::<HtmlTextAreaElement>() {
+            if textarea.default_value().is_ok_and(|default| textarea.value() == default) {
+                textarea.set_value(&draft.value);
+            }
+        }
+    }
+}
+
+pub(super) fn discard_drafts() {
+    DRAFTS.with(|drafts| drafts.borrow_mut().clear());
+}
+
+// The value of a text input or textarea that differs from the one it was
+// rendered with
+fn typed_value(elem: &Element) -> Option<String> {
+    if elem.id().is_empty() {
+        return None;
+    }
+    if let Some(input) = elem.dyn_ref::<HtmlInputElement>() {
+        let value = input.value();
+        return (value != input.default_value()).then_some(value);
+    }
+    let textarea = elem.dyn_ref::<HtmlTextAreaElement>()?;
+    let value = textarea.value();
+    (textarea.default_value().ok()? != value).then_some(value)
+}
+
+fn selection(elem: &HtmlElement) -> Option<Selection> {
+    // Inputs without a caret, like checkboxes, throw or return null
+    let (start, end, direction) = if let Some(input) = elem.dyn_ref::<HtmlInputElement>() {
+        (input.selection_start(), input.selection_end(), input.selection_direction())
+    } else {
+        let textarea = elem.dyn_ref::<HtmlTextAreaElement>()?;
+        (textarea.selection_start(), textarea.selection_end(), textarea.selection_direction())
+    };
+    Some(Selection {
+        start: start.ok().flatten()?,
+        end: end.ok().flatten()?,
+        direction: direction.ok().flatten().unwrap_or_else(|| "none".to_string()),
+    })
+}
+
+fn set_selection(elem: &HtmlElement, selection: &Selection) {
+    let Selection { start, end, direction } = selection;
+    if let Some(input) = elem.dyn_ref::<HtmlInputElement>() {
+        let _ = input.set_selection_range_with_direction(*start, *end, direction);
+    } else if let Some(textarea) = elem.dyn_ref::<HtmlTextAreaElement>() {
+        let _ = textarea.set_selection_range_with_direction(*start, *end, direction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::get_document;
+    use crate::{rsx, vdom};
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::*;
+    use web_sys::HtmlInputElement;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn input(id: &str) -> HtmlInputElement {
+        get_document().get_element_by_id(id).unwrap().unchecked_into()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_focus_and_selection_survive_reordering() {
+        let list = |ids: &[&'static str]| rsx!(div {
+            for id in ids {
+                input { key = id, id = id, type = "text" }
+            }
+        });
+        let container = vdom::mount(list(&["first", "second"]));
+        get_document().body().unwrap().append_child(&container).unwrap();
+
+        let second = input("second");
+        second.set_value("hello");
+        second.focus().unwrap();
+        second.set_selection_range(1, 3).unwrap();
+        vdom::render(&container, list(&["second", "first"]));
+
+        let active = get_document().active_element().unwrap();
+        assert!(active.is_same_node(Some(&second)));
+        assert_eq!((second.selection_start().unwrap(), second.selection_end().unwrap()), (Some(1), Some(3)));
+
+        vdom::unmount(&container);
+        container.remove();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_typed_text_moves_to_a_rebuilt_input() {
+        let container = vdom::mount(rsx!(div { input { id = "draft", type = "text" } }));
+        get_document().body().unwrap().append_child(&container).unwrap();
+        let old = input("draft");
+        old.set_value("half-typed");
+        old.focus().unwrap();
+
+        // A different parent tag rebuilds the input
+        vdom::render(&container, rsx!(section { input { id = "draft", type = "text" } }));
+
+        let new = input("draft");
+        assert!(!new.is_same_node(Some(&old)));
+        assert_eq!(new.value(), "half-typed");
+        assert!(get_document().active_element().unwrap().is_same_node(Some(&new)));
+
+        vdom::unmount(&container);
+        container.remove();
+    }
+}
@@ -0,0 +1,303 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::rc::{Rc, Weak};
+
+use super::context::{with_context, Context};
+use super::hooks::with_hooks;
+use super::{BindingKind, ElementItems, HoleContent, PropValue, StaticElement, StaticNode, VElement, VNode, VTemplate, VText};
+use crate::rsx::Namespace;
+
+// Elements without a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// Renders `node` to HTML without a DOM, so it works on native targets.
+///
+/// The markup is what `render` would put in the document: component
+/// outputs and dynamic children end in an empty comment, bound values are
+/// rendered as they are now and properties become attributes. Listeners,
+/// refs and lifecycle hooks are left out; `hydrate` attaches them in the
+/// browser.
+pub fn render_to_string(node: impl Into<VNode>) -> String {
+    let mut html = String::new();
+    write_node(&mut html, node.into());
+    html
+}
+
+fn write_node(html: &mut String, node: VNode) {
+    match node {
+        VNode::Element(element) => {
+            let VElement { ns, tag, items, children, .. } = *element;
+            write_open_tag(html, tag, &[], &items);
+            for child in children {
+                write_node(html, child);
+            }
+            write_close_tag(html, ns, tag);
+        }
+        VNode::Template(template) => {
+            let VTemplate { skeleton, holes, .. } = *template;
+            let mut holes: Vec<_> = holes.into_iter().map(|hole| (hole.path, Some(hole.content))).collect();
+            write_static(html, skeleton, &mut Vec::new(), &mut holes);
+        }
+        VNode::Text(text) => write_text(html, &text),
+        VNode::Component(mut component) => {
+            let instance = (component.create)(component.props.take().unwrap());
+            // State set from here on has no component to re-render
+            let (output, context) =
+                with_context(Context::current(), || with_hooks(&Rc::default(), Weak::new(), || instance.render()));
+            with_context(context, || write_node(html, output));
+            html.push_str("<!---->");
+        }
+        // Its children are rendered into the target once hydrated
+        VNode::Portal(_) => html.push_str("<!---->"),
+        VNode::Fragment(nodes) => {
+            for node in nodes {
+                write_node(html, node);
+            }
+        }
+    }
+}
+
+fn write_static(
+    html: &mut String,
+    element: &StaticElement,
+    path: &mut Vec<u32>,
+    holes: &mut [(&'static [u32], Option<HoleContent>)],
+) {
+    let items = match take_hole(holes, path) {
+        Some(HoleContent::Element(items)) => items,
+        _ => ElementItems::default(),
+    };
+    write_open_tag(html, element.tag, element.attributes, &items);
+
+    for (index, child) in element.children.iter().enumerate() {
+        path.push(index as u32);
+        match child {
+            StaticNode::Element(child) => write_static(html, child, path, holes),
+            StaticNode::Text(text) => match take_hole(holes, path) {
+                Some(HoleContent::Text(text)) => write_text(html, &text),
+                _ => escape_into(html, text, false),
+            },
+            StaticNode::Marker => {
+                if let Some(HoleContent::Children(children)) = take_hole(holes, path) {
+                    for child in children {
+                        write_node(html, child);
+                    }
+                }
+                html.push_str("<!---->");
+            }
+        }
+        path.pop();
+    }
+
+    write_close_tag(html, element.ns, element.tag);
+}
+
+fn take_hole(holes: &mut [(&'static [u32], Option<HoleContent>)], path: &[u32]) -> Option<HoleContent> {
+    holes.iter_mut().find(|(hole, _)| *hole == path).and_then(|(_, content)| content.take())
+}
+
+fn write_open_tag(html: &mut String, tag: &str, attributes: &[(&'static str, &'static str)], items: &ElementItems) {
+    html.push('<');
+    html.push_str(tag);
+    for (name, value) in rendered_attributes(attributes, items) {
+        html.push(' ');
+        html.push_str(name);
+        // `checked`, `disabled`, ... are written bare
+        if !value.is_empty() {
+            html.push_str("=\"");
+            escape_into(html, &value, true);
+            html.push('"');
+        }
+    }
+    html.push('>');
+}
+
+/// The attributes an element with the static `attributes` and `items` is
+/// rendered with, classes and styles merged into `class` and `style`.
+pub(super) fn rendered_attributes<'a>(
+    attributes: &[(&'static str, &'static str)],
+    items: &'a ElementItems,
+) -> Vec<(&'a str, Cow<'a, str>)> {
+    let mut written: Vec<(&str, Cow<str>)> = attributes.iter().map(|&(name, value)| (name, value.into())).collect();
+
+    for (name, value) in &items.attributes {
+        set_attribute(&mut written, name, value.clone());
+    }
+    for (name, value) in &items.properties {
+        let value = match value {
+            PropValue::Bool(true) => Some(String::new()),
+            PropValue::Bool(false) => None,
+            PropValue::Number(value) => Some(value.to_string()),
+            PropValue::String(value) => Some(value.clone()),
+        };
+        set_attribute(&mut written, name, value);
+    }
+
+    let mut classes = items.classes.clone();
+    let mut styles = items.styles.clone();
+    for binding in &items.bindings {
+        match &binding.kind {
+            BindingKind::Attribute(name, value) => set_attribute(&mut written, name, value()),
+            BindingKind::Style(property, value) => styles.push((property, value())),
+            BindingKind::Class(value) => classes.extend(value()),
+            BindingKind::Text(_) => {}
+        }
+    }
+
+    // Added to what the static attributes set, like the DOM renderer adds
+    // to the class list and inline style
+    let existing = |written: &[(&str, Cow<str>)], name| {
+        written.iter().find(|(written, _)| *written == name).map(|(_, value)| value.to_string())
+    };
+    if !classes.is_empty() {
+        let mut class = existing(&written, "class").unwrap_or_default();
+        for added in classes {
+            if !class.split_whitespace().any(|present| present == added) {
+                if !class.is_empty() {
+                    class.push(' ');
+                }
+                class.push_str(&added);
+            }
+        }
+        set_attribute(&mut written, "class", Some(class));
+    }
+    let styles: Vec<_> = styles.into_iter().filter_map(|(property, value)| Some((property, value?))).collect();
+    if !styles.is_empty() {
+        let mut style = existing(&written, "style").unwrap_or_default();
+        for (property, value) in styles {
+            if !style.is_empty() {
+                if !style.ends_with(';') {
+                    style.push(';');
+                }
+                style.push(' ');
+            }
+            let _ = write!(style, "{}: {};", property, value);
+        }
+        set_attribute(&mut written, "style", Some(style));
+    }
+    written
+}
+
+// Later values replace earlier ones, as they do on the DOM
+fn set_attribute<'a>(written: &mut Vec<(&'a str, Cow<'a, str>)>, name: &'a str, value: Option<String>) {
+    written.retain(|(written, _)| *written != name);
+    if let Some(value) = value {
+        written.push((name, value.into()));
+    }
+}
+
+fn write_close_tag(html: &mut String, ns: Namespace, tag: &str) {
+    if ns == Namespace::Html && VOID_ELEMENTS.contains(&tag) {
+        return;
+    }
+    let _ = write!(html, "</{}>", tag);
+}
+
+fn write_text(html: &mut String, text: &VText) {
+    match &text.binding {
+        Some(binding) => match &binding.kind {
+            BindingKind::Text(value) => escape_into(html, &value(), false),
+            _ => escape_into(html, &text.text, false),
+        },
+        None => escape_into(html, &text.text, false),
+    }
+}
+
+fn escape_into(html: &mut String, text: &str, attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if attribute => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+    }
+}
+
+// Plain `#[test]`s: rendering to a string needs no browser
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_loader::{Article, Content};
+    use crate::rsx;
+    use crate::signals::Signal;
+
+    #[test]
+    fn test_text_and_attributes_are_escaped() {
+        let text = "<b>\"Tom\" & Jerry</b>";
+        let html = render_to_string(rsx!(p { title = text, @text }));
+
+        assert_eq!(
+            html,
+            "<p title=\"&lt;b&gt;&quot;Tom&quot; &amp; Jerry&lt;/b&gt;\">&lt;b&gt;\"Tom\" &amp; Jerry&lt;/b&gt;</p>"
+        );
+    }
+
+    #[test]
+    fn test_items_and_bindings_render_their_current_values() {
+        let count = Signal::new(3);
+        let html = render_to_string(rsx!(div {
+            class = "box",
+            class:active = true,
+            style = { color: "red", "--count": count },
+            input { type = "checkbox", .checked = true, disabled = false },
+            span { @count }
+        }));
+
+        assert_eq!(
+            html,
+            "<div class=\"box active\" style=\"color: red; --count: 3;\">\
+             <input type=\"checkbox\" checked><span>3</span></div>"
+        );
+    }
+
+    #[test]
+    fn test_nav_renders_without_a_document() {
+        let html = render_to_string(crate::render_nav());
+
+        assert_eq!(
+            html,
+            "<nav class=\"main-nav\"><ul><li>Home</li><li>Articles</li>\
+             <li>About</li><li>Not Found</li></ul></nav>"
+        );
+    }
+
+    #[test]
+    fn test_articles_render_their_content() {
+        let content = Content {
+            articles: vec![Article {
+                title: "Fish & Chips".into(),
+                content: "Crispy.".into(),
+                date: "2024-01-01".into(),
+                author: String::new(),
+                tags: vec!["food".into()],
+            }],
+        };
+        let html = render_to_string(crate::render_articles(content));
+
+        assert!(html.starts_with("<section id=\"articles\" class=\"articles-section\">"));
+        assert!(html.contains("<h2>Fish &amp; Chips</h2>"));
+        assert!(html.contains("<span>2024-01-01</span>"));
+        assert!(html.contains("<span class=\"tag\">food</span>"));
+        assert!(html.contains("<p>Crispy.</p>"));
+    }
+
+    #[test]
+    fn test_todo_list_renders_components_and_items() {
+        crate::state::STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.todos.clear();
+            state.todos.push(crate::state::app_state::Todo { id: 0, text: "a < b".into(), completed: true });
+        });
+        let html = render_to_string(crate::todo::todo_list());
+
+        assert!(html.starts_with("<div id=\"todo-list\"><div class=\"input-container\"><input"));
+        assert!(html.contains("placeholder=\"Add new todo\""));
+        assert!(html.contains("class=\"todo-item completed\""));
+        assert!(html.contains("<input class=\"todo-checkbox\" type=\"checkbox\" aria-label=\"Mark todo as completed\" checked>"));
+        assert!(html.contains("<span class=\"todo-text\">a &lt; b</span>"));
+    }
+}